@@ -0,0 +1,98 @@
+// Case-insensitive project text search: the chunked byte-level matcher against
+// the line-by-line search it replaced (each line lowercased into a new String).
+// Inputs are in memory so only the matching is measured. Run with
+// `cargo bench --bench matcher`.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use std::io::{self, BufRead, BufReader, Cursor, Read};
+
+#[allow(dead_code)]
+#[path = "../src/query.rs"]
+mod query;
+#[allow(dead_code)]
+#[path = "../src/scan.rs"]
+mod scan;
+
+use query::Query;
+
+/// The previous implementation: read lines as UTF-8 and search each one, with the
+/// tail of the previous line prepended, in a lowercased copy
+fn line_based_contains(reader: impl Read, query: &Query) -> io::Result<bool> {
+    let search_len = query.labels.iter().map(|l| l.to_lowercase().len()).max().unwrap_or(0);
+    let mut overlap = String::new();
+    let mut found = vec![false; query.terms.len()];
+    for line in BufReader::new(reader).lines() {
+        let line = line?;
+        let combined = format!("{}{}", overlap, line);
+        for (i, term) in query.terms.iter().enumerate() {
+            if !found[i] && term.is_match(&combined) {
+                found[i] = true;
+            }
+        }
+        if let Some(result) = query.evaluate(&found, false) {
+            return Ok(result);
+        }
+        overlap = if search_len == 0 {
+            String::new()
+        } else {
+            let chars: Vec<char> = combined.chars().collect();
+            chars[chars.len().saturating_sub(search_len - 1)..].iter().collect()
+        };
+    }
+    Ok(query.evaluate(&found, true).unwrap_or(false))
+}
+
+/// About `size` bytes of project-like XML, one element per line, ending with `tail`
+fn project_xml(size: usize, tail: &str) -> Vec<u8> {
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\" ?>\n<PremiereData Version=\"3\">\n");
+    let mut i = 0;
+    while xml.len() < size {
+        xml.push_str(&format!(
+            "\t<Media ObjectUID=\"{:08x}\"><FilePath>D:\\Footage\\Day {}\\A{:03}_C{:03}.MP4</FilePath><Title>Interview take {}</Title></Media>\n",
+            i, i % 30, i % 7, i % 500, i
+        ));
+        i += 1;
+    }
+    xml.push_str(tail);
+    xml.push_str("\n</PremiereData>\n");
+    xml.into_bytes()
+}
+
+fn matcher(c: &mut Criterion) {
+    let size = 8 * 1024 * 1024;
+    let found_at_end = project_xml(size, "<Name>Clair de Lune (final mix)</Name>");
+    let inputs = [
+        ("match_at_end", found_at_end.clone()),
+        ("no_match", project_xml(size, "")),
+        // Minified XML: the whole project on one line
+        ("single_line", found_at_end.iter().copied().filter(|&b| b != b'\n').collect()),
+    ];
+
+    let queries = [
+        ("literal", Query::parse("clair de lune", false).unwrap()),
+        ("boolean", Query::parse("clair de lune AND NOT temp AND interview", false).unwrap()),
+    ];
+
+    let mut group = c.benchmark_group("contains");
+    group.sample_size(10);
+    group.throughput(Throughput::Bytes(size as u64));
+    for (query_name, query) in &queries {
+        for (input_name, input) in &inputs {
+            let id = format!("{}/{}", query_name, input_name);
+            assert_eq!(
+                line_based_contains(Cursor::new(input), query).unwrap(),
+                scan::stream_contains(Cursor::new(input), query).unwrap()
+            );
+            group.bench_with_input(BenchmarkId::new("line_based", &id), input, |b, input| {
+                b.iter(|| line_based_contains(Cursor::new(input), query).unwrap())
+            });
+            group.bench_with_input(BenchmarkId::new("chunked", &id), input, |b, input| {
+                b.iter(|| scan::stream_contains(Cursor::new(input), query).unwrap())
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, matcher);
+criterion_main!(benches);
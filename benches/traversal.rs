@@ -0,0 +1,95 @@
+// Directory traversal: the sequential WalkDir walk against the parallel walker,
+// over a synthetic tree of several roots (projects, other files and an excluded
+// folder in each). Run with `cargo bench --bench traversal`; set
+// PREMIERE_HUNTER_BENCH_DIR to build the tree on another volume, such as a
+// mounted network share, instead of the system temp directory.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+#[allow(dead_code)]
+#[path = "../src/discover.rs"]
+mod discover;
+#[allow(dead_code)]
+#[path = "../src/rules.rs"]
+mod rules;
+
+use discover::{discover_files, DiscoverOptions};
+
+const ROOTS: usize = 4;
+const DIRS: usize = 16;
+const SUBDIRS: usize = 16;
+const PROJECTS_PER_DIR: usize = 4;
+const OTHER_FILES_PER_DIR: usize = 8;
+
+/// ROOTS x DIRS x SUBDIRS folders of projects and other files, plus an excluded "Archive" folder per root
+fn build_tree(base: &Path) -> Vec<PathBuf> {
+    let roots: Vec<PathBuf> = (0..ROOTS).map(|r| base.join(format!("root{}", r))).collect();
+    for root in &roots {
+        for d in 0..DIRS {
+            for s in 0..SUBDIRS {
+                let dir = root.join(format!("d{}", d)).join(format!("s{}", s));
+                fs::create_dir_all(&dir).unwrap();
+                for p in 0..PROJECTS_PER_DIR {
+                    fs::write(dir.join(format!("edit{}.prproj", p)), b"<PremiereData/>").unwrap();
+                }
+                for f in 0..OTHER_FILES_PER_DIR {
+                    fs::write(dir.join(format!("notes{}.txt", f)), b"notes").unwrap();
+                }
+            }
+        }
+        let archive = root.join("Archive");
+        fs::create_dir_all(&archive).unwrap();
+        for p in 0..PROJECTS_PER_DIR * 16 {
+            fs::write(archive.join(format!("old{}.prproj", p)), b"<PremiereData/>").unwrap();
+        }
+    }
+    roots
+}
+
+fn count_projects(roots: &[PathBuf], walk_threads: usize) -> usize {
+    let options = DiscoverOptions {
+        follow_links: false,
+        exclude_dirs: Some(vec!["archive".to_string()]),
+        extensions: vec!["prproj".to_string()],
+        min_size_bytes: None,
+        modified_after: None,
+        modified_before: None,
+        max_depth: None,
+        rules: Default::default(),
+        walk_threads,
+    };
+    let interrupted = AtomicBool::new(false);
+    let found = AtomicUsize::new(0);
+    discover_files(roots, &options, &interrupted, |_| {
+        found.fetch_add(1, Ordering::Relaxed);
+        true
+    });
+    found.into_inner()
+}
+
+fn traversal(c: &mut Criterion) {
+    let base = std::env::var_os("PREMIERE_HUNTER_BENCH_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(std::env::temp_dir)
+        .join(format!("premiere-hunter-bench-{}", std::process::id()));
+    let roots = build_tree(&base);
+    let expected = ROOTS * DIRS * SUBDIRS * PROJECTS_PER_DIR;
+
+    let mut group = c.benchmark_group("discover_files");
+    group.sample_size(20);
+    for walk_threads in [1, 2, 4, 8] {
+        assert_eq!(count_projects(&roots, walk_threads), expected);
+        group.bench_with_input(BenchmarkId::new("walk_threads", walk_threads), &walk_threads, |b, &n| {
+            b.iter(|| count_projects(&roots, n))
+        });
+    }
+    group.finish();
+
+    fs::remove_dir_all(&base).ok();
+}
+
+criterion_group!(benches, traversal);
+criterion_main!(benches);
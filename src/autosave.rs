@@ -0,0 +1,210 @@
+// Auto-save grouping (--autosave): Premiere writes timestamped copies of a project
+// into an "Adobe Premiere Pro Auto-Save" folder. Results for those copies are held
+// back during the scan and reported once per originating project at the end.
+
+use clap::ValueEnum;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::output::{AutoSaveVersion, Record};
+
+/// Folder Premiere creates for auto-save copies, next to the project or in a configured location
+const AUTO_SAVE_FOLDER: &str = "Adobe Premiere Pro Auto-Save";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AutoSaveMode {
+    /// Report only the newest matching copy of each project
+    Newest,
+    /// Report the newest matching copy plus every matching version with its timestamp
+    All,
+}
+
+/// Project name and save time parsed from an auto-save file name
+struct AutoSaveName {
+    project: String,
+    /// YYYY-MM-DD HH:MM:SS, so it sorts chronologically
+    saved: String,
+}
+
+struct HeldCopy {
+    saved: String,
+    path: PathBuf,
+    record: Record,
+}
+
+pub struct AutoSaves {
+    mode: AutoSaveMode,
+    /// "Wedding--12-01-2024_10-30-00" (month first) or "Wedding-2024-12-01_10-30-00"
+    month_first: Regex,
+    year_first: Regex,
+    /// Held copies by (auto-save folder, project name)
+    groups: Mutex<HashMap<(PathBuf, String), Vec<HeldCopy>>>,
+}
+
+impl AutoSaves {
+    pub fn new(mode: AutoSaveMode) -> AutoSaves {
+        AutoSaves {
+            mode,
+            month_first: Regex::new(r"^(.+?)--?(\d{2})-(\d{2})-(\d{4})_(\d{2})-(\d{2})-(\d{2})$").unwrap(),
+            year_first: Regex::new(r"^(.+?)--?(\d{4})-(\d{2})-(\d{2})_(\d{2})-(\d{2})-(\d{2})$").unwrap(),
+            groups: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn parse(&self, path: &Path) -> Option<AutoSaveName> {
+        let folder = path.parent()?.file_name()?.to_str()?;
+        if !folder.eq_ignore_ascii_case(AUTO_SAVE_FOLDER) {
+            return None;
+        }
+        let stem = path.file_stem()?.to_str()?;
+        // Capture groups holding year, month and day
+        let (c, [y, m, d]) = match self.month_first.captures(stem) {
+            Some(c) => (c, [4, 2, 3]),
+            None => (self.year_first.captures(stem)?, [2, 3, 4]),
+        };
+        let saved = format!("{}-{}-{} {}:{}:{}", &c[y], &c[m], &c[d], &c[5], &c[6], &c[7]);
+        Some(AutoSaveName { project: c[1].to_string(), saved })
+    }
+
+    /// Hold the result for an auto-save copy until `finish`; any other result is handed back
+    pub fn hold(&self, path: &Path, record: Record) -> Option<Record> {
+        let Some(name) = self.parse(path) else {
+            return Some(record);
+        };
+        let folder = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        let copy = HeldCopy { saved: name.saved, path: path.to_path_buf(), record };
+        self.groups
+            .lock()
+            .unwrap()
+            .entry((folder, name.project))
+            .or_default()
+            .push(copy);
+        None
+    }
+
+    /// One record per project, ordered by folder and name: the newest copy's result,
+    /// with the matching versions listed newest first in `all` mode
+    pub fn finish(self) -> Vec<Record> {
+        let mut groups: Vec<((PathBuf, String), Vec<HeldCopy>)> = self.groups.into_inner().unwrap().into_iter().collect();
+        groups.sort_by(|a, b| a.0.cmp(&b.0));
+
+        groups
+            .into_iter()
+            .filter_map(|((folder, project), mut copies)| {
+                copies.sort_by(|a, b| b.saved.cmp(&a.saved).then_with(|| b.path.cmp(&a.path)));
+                let matched = copies.len();
+                let versions = (self.mode == AutoSaveMode::All).then(|| {
+                    copies
+                        .iter()
+                        .map(|c| AutoSaveVersion { saved: c.saved.clone(), path: c.path.display().to_string() })
+                        .collect()
+                });
+                let newest = copies.into_iter().next()?;
+                // The auto-save folder normally sits next to the project it backs up
+                let original = folder
+                    .parent()
+                    .map(|dir| dir.join(format!("{}.prproj", project)))
+                    .filter(|p| p.is_file())
+                    .map(|p| p.display().to_string());
+                Some(Record::AutoSave {
+                    project,
+                    original,
+                    saved: newest.saved,
+                    matched,
+                    versions,
+                    record: Box::new(newest.record),
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// (project, saved) for a file in an auto-save folder
+    fn parse(file_name: &str) -> Option<(String, String)> {
+        let path = Path::new("/jobs/Wedding").join(AUTO_SAVE_FOLDER).join(file_name);
+        let name = AutoSaves::new(AutoSaveMode::Newest).parse(&path)?;
+        Some((name.project, name.saved))
+    }
+
+    fn named(project: &str, saved: &str) -> Option<(String, String)> {
+        Some((project.to_string(), saved.to_string()))
+    }
+
+    #[test]
+    fn month_first_names() {
+        assert_eq!(parse("Project--12-01-2024_10-30-00.prproj"), named("Project", "2024-12-01 10:30:00"));
+        assert_eq!(parse("Project-12-01-2024_10-30-00.prproj"), named("Project", "2024-12-01 10:30:00"));
+    }
+
+    #[test]
+    fn year_first_names() {
+        assert_eq!(parse("Wedding-2024-12-01_10-30-00.prproj"), named("Wedding", "2024-12-01 10:30:00"));
+        assert_eq!(parse("Wedding--2024-12-01_23-59-59.prproj"), named("Wedding", "2024-12-01 23:59:59"));
+    }
+
+    #[test]
+    fn project_names_containing_double_hyphens() {
+        assert_eq!(parse("Smith--Jones Wedding--12-01-2024_10-30-00.prproj"), named("Smith--Jones Wedding", "2024-12-01 10:30:00"));
+        assert_eq!(parse("Cut--v2--2024-12-01_10-30-00.prproj"), named("Cut--v2", "2024-12-01 10:30:00"));
+        // Only the last timestamp is the save time
+        assert_eq!(
+            parse("Edit--01-01-2020_00-00-00--12-01-2024_10-30-00.prproj"),
+            named("Edit--01-01-2020_00-00-00", "2024-12-01 10:30:00")
+        );
+        assert_eq!(parse("Cut---12-01-2024_10-30-00.prproj"), named("Cut-", "2024-12-01 10:30:00"));
+    }
+
+    #[test]
+    fn names_without_a_save_time() {
+        assert_eq!(parse("Wedding.prproj"), None);
+        assert_eq!(parse("Wedding--12-01-24_10-30-00.prproj"), None);
+        assert_eq!(parse("Wedding--12-01-2024.prproj"), None);
+        assert_eq!(parse("12-01-2024_10-30-00.prproj"), None);
+    }
+
+    #[test]
+    fn only_files_in_an_auto_save_folder() {
+        let autosaves = AutoSaves::new(AutoSaveMode::Newest);
+        let stamped = "Project--12-01-2024_10-30-00.prproj";
+        assert!(autosaves.parse(&Path::new("/jobs").join(stamped)).is_none());
+        assert!(autosaves.parse(&Path::new("/jobs/Auto-Save").join(stamped)).is_none());
+        assert!(autosaves.parse(&Path::new("/jobs").join(AUTO_SAVE_FOLDER).join("old").join(stamped)).is_none());
+        assert!(autosaves.parse(&Path::new("/jobs/adobe premiere pro auto-save").join(stamped)).is_some());
+        assert!(autosaves.parse(Path::new(stamped)).is_none());
+    }
+
+    #[test]
+    fn newest_copy_per_project() {
+        let autosaves = AutoSaves::new(AutoSaveMode::All);
+        let folder = Path::new("/jobs").join(AUTO_SAVE_FOLDER);
+        let record = |path: &Path| Record::Match {
+            path: path.display().to_string(),
+            snippet: None,
+            scopes: None,
+            occurrences: None,
+        };
+        for name in ["Edit--12-01-2024_10-30-00", "Edit-2025-01-02_09-00-00", "Edit--11-30-2024_18-00-00", "Other--12-01-2024_10-30-00"] {
+            let path = folder.join(format!("{}.prproj", name));
+            assert!(autosaves.hold(&path, record(&path)).is_none());
+        }
+        let elsewhere = Path::new("/jobs/Edit.prproj");
+        assert!(autosaves.hold(elsewhere, record(elsewhere)).is_some());
+
+        let records = serde_json::to_value(autosaves.finish()).unwrap();
+        assert_eq!(records[0]["project"], "Edit");
+        assert_eq!(records[0]["saved"], "2025-01-02 09:00:00");
+        assert_eq!(records[0]["matched"], 3);
+        let saved: Vec<&str> = records[0]["versions"].as_array().unwrap().iter().map(|v| v["saved"].as_str().unwrap()).collect();
+        assert_eq!(saved, ["2025-01-02 09:00:00", "2024-12-01 10:30:00", "2024-11-30 18:00:00"]);
+        assert_eq!(records[1]["project"], "Other");
+        assert_eq!(records[1]["matched"], 1);
+    }
+}
@@ -0,0 +1,297 @@
+// Project packaging (`collect` subcommand): copy a project and every media file it
+// references into one folder, relink the copy to the collected media, and write a
+// manifest of what was (and wasn't) collected, for handing work to another studio.
+
+use clap::{Args, ValueEnum};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+use crate::media::{asset_fs_path, check_project_media, is_absolute_asset, resolve_relative, MediaStatus};
+use crate::remap::{rewrite_media_paths, same_file};
+
+/// Folder inside the target that receives the media
+const MEDIA_FOLDER: &str = "Media";
+const MANIFEST_NAME: &str = "collect-manifest.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Layout {
+    /// Keep each file's original folders, under a folder per drive or server
+    Mirrored,
+    /// Put every file directly in the media folder, numbering clashing names
+    Flattened,
+}
+
+#[derive(Args, Debug)]
+pub struct CollectArgs {
+    /// Project to collect
+    #[arg(value_name = "PROJECT")]
+    pub project: PathBuf,
+
+    /// Folder to collect into (created if needed)
+    #[arg(long, value_name = "DIR")]
+    pub to: PathBuf,
+
+    /// How media files are laid out under <DIR>/Media
+    #[arg(long, value_enum, default_value_t = Layout::Mirrored)]
+    pub layout: Layout,
+
+    /// Print the plan without copying anything
+    #[arg(long, default_value_t = false)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct ManifestEntry {
+    /// Asset path as stored in the project
+    asset: String,
+    /// Where it was copied from (not set when missing)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    source: Option<String>,
+    /// Path of the copy, relative to the collect folder
+    #[serde(skip_serializing_if = "Option::is_none")]
+    destination: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    size: Option<u64>,
+    status: MediaStatus,
+}
+
+#[derive(Debug, Serialize)]
+struct Manifest {
+    project: String,
+    collected_project: String,
+    layout: Layout,
+    media_files: usize,
+    total_bytes: u64,
+    missing: usize,
+    assets: Vec<ManifestEntry>,
+}
+
+/// Folders and file name of `source` below the media folder, in mirrored layout:
+/// "E:\Shoot\A001.mov" -> E/Shoot/A001.mov, "\\nas\shoot\A001.mov" -> nas/shoot/A001.mov
+fn mirrored_path(source: &Path) -> PathBuf {
+    let mut relative = PathBuf::new();
+    for component in source.components() {
+        match component {
+            Component::Prefix(prefix) => {
+                let text = prefix.as_os_str().to_string_lossy().replace(['?', ':'], "");
+                for part in text.split(['\\', '/']).filter(|p| !p.is_empty() && *p != "UNC") {
+                    relative.push(part);
+                }
+            }
+            Component::Normal(part) => relative.push(part),
+            Component::RootDir | Component::CurDir | Component::ParentDir => {}
+        }
+    }
+    relative
+}
+
+/// File name of `source`, numbered when an earlier file already took it ("A001 (2).mov")
+fn flattened_path(source: &Path, taken: &mut HashSet<String>) -> PathBuf {
+    let name = source.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let stem = source.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    let ext = source.extension().map(|e| format!(".{}", e.to_string_lossy())).unwrap_or_default();
+    let mut candidate = name;
+    let mut n = 2;
+    while !taken.insert(candidate.to_lowercase()) {
+        candidate = format!("{} ({}){}", stem, n, ext);
+        n += 1;
+    }
+    PathBuf::from(candidate)
+}
+
+/// Relative path as written in the project: ".\Media\E\Shoot\A001.mov"
+fn project_relative(path: &Path) -> String {
+    let parts: Vec<String> = path.components().map(|c| c.as_os_str().to_string_lossy().to_string()).collect();
+    format!(".\\{}", parts.join("\\"))
+}
+
+/// Run the `collect` subcommand; returns the process exit code
+/// (1 when media is missing, so the collection is incomplete, or on errors)
+pub fn run(args: &CollectArgs) -> i32 {
+    match collect(args) {
+        Ok(missing) if missing > 0 => 1,
+        Ok(_) => 0,
+        Err(e) => {
+            eprintln!("Error: cannot collect {}: {}", args.project.display(), e);
+            1
+        }
+    }
+}
+
+/// Collect the project; returns how many media files were missing
+fn collect(args: &CollectArgs) -> io::Result<usize> {
+    let project_dir = args.project.parent().unwrap_or_else(|| Path::new(".")).to_path_buf();
+    let file_name = args.project.file_name().ok_or_else(|| io::Error::other("not a project file"))?;
+    let collected_project = args.to.join(file_name);
+    if same_file(&args.project, &collected_project) {
+        return Err(io::Error::other("the target folder holds the project itself; choose another --to"));
+    }
+
+    // Where each asset is on disk, then where its copy goes (one copy per source file)
+    let checks = check_project_media(&args.project, None)?;
+    let mut destinations: HashMap<PathBuf, PathBuf> = HashMap::new();
+    let mut taken: HashSet<String> = HashSet::new();
+    let mut entries: Vec<ManifestEntry> = Vec::new();
+    let mut sources: Vec<PathBuf> = Vec::new();
+    // Lowercased asset path -> its source file
+    let mut asset_sources: HashMap<String, PathBuf> = HashMap::new();
+    for check in checks {
+        let source = match check.status {
+            MediaStatus::Online => Some(asset_fs_path(&check.asset)),
+            MediaStatus::FoundViaRelative => check.resolved.as_ref().map(PathBuf::from),
+            MediaStatus::Missing => None,
+        };
+        let Some(source) = source.map(|s| fs::canonicalize(&s).unwrap_or(s)) else {
+            entries.push(ManifestEntry { asset: check.asset, source: None, destination: None, size: None, status: check.status });
+            continue;
+        };
+        let destination = destinations
+            .entry(source.clone())
+            .or_insert_with(|| {
+                sources.push(source.clone());
+                let below = match args.layout {
+                    Layout::Mirrored => mirrored_path(&source),
+                    Layout::Flattened => flattened_path(&source, &mut taken),
+                };
+                Path::new(MEDIA_FOLDER).join(below)
+            })
+            .clone();
+        asset_sources.insert(check.asset.to_lowercase(), source.clone());
+        entries.push(ManifestEntry {
+            asset: check.asset,
+            size: fs::metadata(&source).ok().map(|m| m.len()),
+            source: Some(source.display().to_string()),
+            destination: Some(destination.display().to_string()),
+            status: check.status,
+        });
+    }
+
+    if !args.dry_run {
+        fs::create_dir_all(&args.to)?;
+    }
+    let target = fs::canonicalize(&args.to).unwrap_or_else(|_| args.to.clone());
+
+    // Copy the media
+    let mut total_bytes = 0;
+    for source in &sources {
+        let destination = &destinations[source];
+        println!("  {}\n    -> {}", source.display(), destination.display());
+        if args.dry_run {
+            total_bytes += fs::metadata(source).map(|m| m.len()).unwrap_or(0);
+            continue;
+        }
+        let copy = target.join(destination);
+        if let Some(parent) = copy.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        total_bytes += fs::copy(source, &copy).map_err(|e| io::Error::new(e.kind(), format!("{}: {}", source.display(), e)))?;
+    }
+
+    // Relink the copy: absolute paths to the collected files, relative paths from the
+    // collected project's folder
+    let relink = |path: &str| -> Option<String> {
+        if is_absolute_asset(path) {
+            let source = asset_sources.get(&path.to_lowercase())?;
+            return Some(target.join(&destinations[source]).display().to_string());
+        }
+        let source = resolve_relative(&project_dir, path)?;
+        let source = fs::canonicalize(&source).unwrap_or(source);
+        destinations.get(&source).map(|d| project_relative(d))
+    };
+    let dest = (!args.dry_run).then_some(collected_project.as_path());
+    let report = rewrite_media_paths(&args.project, dest, relink)?;
+
+    let missing: Vec<&ManifestEntry> = entries.iter().filter(|e| e.status == MediaStatus::Missing).collect();
+    for entry in &missing {
+        println!("  ✗ missing: {}", entry.asset);
+    }
+    let missing = missing.len();
+
+    let manifest = Manifest {
+        project: args.project.display().to_string(),
+        collected_project: target.join(file_name).display().to_string(),
+        layout: args.layout,
+        media_files: sources.len(),
+        total_bytes,
+        missing,
+        assets: entries,
+    };
+    let manifest_path = target.join(MANIFEST_NAME);
+    if !args.dry_run {
+        let json = serde_json::to_string_pretty(&manifest).map_err(io::Error::other)?;
+        fs::write(&manifest_path, json)?;
+    }
+
+    println!("\n{}", "=".repeat(60));
+    println!("Media files collected: {} ({:.1} MB)", sources.len(), total_bytes as f64 / (1024.0 * 1024.0));
+    println!("Path values relinked: {}", report.values_changed());
+    println!("Missing media: {}", missing);
+    if args.dry_run {
+        println!("Dry run: nothing copied");
+    } else {
+        println!("Project: {}", manifest.collected_project);
+        println!("Manifest: {}", manifest_path.display());
+    }
+    println!("{}", "=".repeat(60));
+    Ok(missing)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parts(parts: &[&str]) -> PathBuf {
+        parts.iter().collect()
+    }
+
+    #[test]
+    fn mirrored_path_keeps_the_folders_below_the_root() {
+        let mirrored = mirrored_path(Path::new("/Volumes/Shoot/Day 1/A001.mov"));
+        assert_eq!(mirrored, parts(&["Volumes", "Shoot", "Day 1", "A001.mov"]));
+        assert!(mirrored.is_relative());
+    }
+
+    // Drive and server prefixes are only parsed as such on Windows
+    #[cfg(windows)]
+    #[test]
+    fn mirrored_path_names_a_folder_after_the_drive_or_server() {
+        for (source, expected) in [
+            (r"E:\Shoot\A001.mov", &["E", "Shoot", "A001.mov"][..]),
+            (r"\\?\E:\Shoot\A001.mov", &["E", "Shoot", "A001.mov"][..]),
+            (r"\\nas\shoot\Day 1\A001.mov", &["nas", "shoot", "Day 1", "A001.mov"][..]),
+            (r"\\?\UNC\nas\shoot\Day 1\A001.mov", &["nas", "shoot", "Day 1", "A001.mov"][..]),
+        ] {
+            let mirrored = mirrored_path(Path::new(source));
+            assert_eq!(mirrored, parts(expected), "{}", source);
+            assert!(mirrored.is_relative(), "{}", source);
+        }
+    }
+
+    #[test]
+    fn flattened_path_numbers_clashing_names() {
+        let mut taken = HashSet::new();
+        let mut flatten = |source: &str| flattened_path(Path::new(source), &mut taken);
+        assert_eq!(flatten("/a/A001.mov"), PathBuf::from("A001.mov"));
+        assert_eq!(flatten("/b/A001.mov"), PathBuf::from("A001 (2).mov"));
+        assert_eq!(flatten("/c/A001.mov"), PathBuf::from("A001 (3).mov"));
+        // Names clash regardless of case, and the original case is kept
+        assert_eq!(flatten("/d/a001.MOV"), PathBuf::from("a001 (4).MOV"));
+        assert_eq!(flatten("/a/A002.mov"), PathBuf::from("A002.mov"));
+        assert_eq!(flatten("/a/README"), PathBuf::from("README"));
+        assert_eq!(flatten("/b/README"), PathBuf::from("README (2)"));
+    }
+
+    #[test]
+    fn flattened_path_skips_numbers_already_taken() {
+        let mut taken = HashSet::new();
+        let mut flatten = |source: &str| flattened_path(Path::new(source), &mut taken);
+        assert_eq!(flatten("/a/Mix (2).wav"), PathBuf::from("Mix (2).wav"));
+        assert_eq!(flatten("/a/Mix.wav"), PathBuf::from("Mix.wav"));
+        assert_eq!(flatten("/b/Mix.wav"), PathBuf::from("Mix (3).wav"));
+        assert_eq!(flatten("/b/Mix (2).wav"), PathBuf::from("Mix (2) (2).wav"));
+    }
+}
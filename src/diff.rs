@@ -0,0 +1,527 @@
+// Structural project comparison (`diff` subcommand): what changed between two
+// versions of a project in terms an editor recognizes (media, sequences, clips on
+// tracks, markers, clip names) instead of a raw XML diff, which is dominated by
+// regenerated GUIDs, timestamps and reordered settings.
+//
+// Sequences and master clips keep their ObjectUID across saves, so they are paired
+// by UID first and by name otherwise. Media is compared by file path, with media
+// that kept its UID under a new path reported as relinked, and clips on a track are
+// paired by source and timing.
+
+use clap::Args;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::io;
+use std::path::PathBuf;
+
+use crate::media_asset_path;
+use crate::project::{ticks_to_timecode, ClipSource, Marker, Project, Sequence, Track, TrackItem};
+
+#[derive(Args, Debug)]
+pub struct DiffArgs {
+    /// Older version of the project
+    #[arg(value_name = "OLD")]
+    pub old: PathBuf,
+
+    /// Newer version of the project
+    #[arg(value_name = "NEW")]
+    pub new: PathBuf,
+}
+
+/// Number of changes per kind, for the summary
+#[derive(Debug, Default)]
+struct Counts {
+    media: usize,
+    sequences: usize,
+    tracks: usize,
+    clips: usize,
+    markers: usize,
+    renames: usize,
+}
+
+impl Counts {
+    fn total(&self) -> usize {
+        self.media + self.sequences + self.tracks + self.clips + self.markers + self.renames
+    }
+}
+
+/// Media path, or the title for media without one (e.g. synthetic clips)
+fn media_label(project: &Project, index: usize) -> String {
+    let media = &project.media[index];
+    media
+        .actual_media_file_path
+        .as_deref()
+        .or(media.file_path.as_deref())
+        .and_then(media_asset_path)
+        .unwrap_or_else(|| media.title.clone())
+}
+
+fn media_key(project: &Project, index: usize) -> String {
+    media_label(project, index).to_lowercase()
+}
+
+/// What a track item plays, comparable across the two projects
+fn source_key(project: &Project, item: &TrackItem) -> String {
+    match item.source {
+        Some(ClipSource::Media(i)) => format!("media:{}", media_key(project, i)),
+        Some(ClipSource::Sequence(i)) => format!("sequence:{}", project.sequences[i].uid),
+        None => format!("name:{}", item.name),
+    }
+}
+
+/// Timecode in the sequence's frame rate, or raw ticks when it has none
+fn timecode(seq: &Sequence, ticks: i64) -> String {
+    match seq.frame_rate {
+        Some(rate) => ticks_to_timecode(ticks, rate),
+        None => format!("{} ticks", ticks),
+    }
+}
+
+/// Pair items of `old` and `new` by UID, then by name; returns matched index pairs
+/// and the unmatched indices on each side
+fn pair_by_uid_then_name<T>(
+    old: &[T],
+    new: &[T],
+    uid: impl Fn(&T) -> &str,
+    name: impl Fn(&T) -> &str,
+) -> (Vec<(usize, usize)>, Vec<usize>, Vec<usize>) {
+    let mut pairs = Vec::new();
+    let mut new_left: Vec<usize> = (0..new.len()).collect();
+    let mut old_left = Vec::new();
+    for (i, o) in old.iter().enumerate() {
+        if let Some(pos) = new_left.iter().position(|&j| !uid(o).is_empty() && uid(&new[j]) == uid(o)) {
+            pairs.push((i, new_left.remove(pos)));
+        } else {
+            old_left.push(i);
+        }
+    }
+    let mut removed = Vec::new();
+    for i in old_left {
+        if let Some(pos) = new_left.iter().position(|&j| name(&new[j]) == name(&old[i])) {
+            pairs.push((i, new_left.remove(pos)));
+        } else {
+            removed.push(i);
+        }
+    }
+    (pairs, removed, new_left)
+}
+
+fn diff_media(old: &Project, new: &Project, counts: &mut Counts) -> Vec<String> {
+    let keys = |project: &Project| -> HashMap<String, String> {
+        (0..project.media.len())
+            .map(|i| (media_key(project, i), media_label(project, i)))
+            .collect()
+    };
+    let (old_keys, new_keys) = (keys(old), keys(new));
+
+    // A path that went away and one that appeared under the same media UID (e.g.
+    // Replace Footage or Link Media) is one relinked media item
+    let new_by_uid: HashMap<&str, usize> = new
+        .media
+        .iter()
+        .enumerate()
+        .filter(|(_, m)| !m.uid.is_empty())
+        .map(|(j, m)| (m.uid.as_str(), j))
+        .collect();
+    let mut relinked = BTreeSet::new();
+    for (i, media) in old.media.iter().enumerate() {
+        let Some(&j) = new_by_uid.get(media.uid.as_str()) else {
+            continue;
+        };
+        let (old_key, new_key) = (media_key(old, i), media_key(new, j));
+        if !new_keys.contains_key(&old_key) && !old_keys.contains_key(&new_key) {
+            relinked.insert((old_key, new_key));
+        }
+    }
+    let relinked_from: HashSet<&String> = relinked.iter().map(|(o, _)| o).collect();
+    let relinked_to: HashSet<&String> = relinked.iter().map(|(_, n)| n).collect();
+
+    let removed: BTreeSet<&String> = old_keys
+        .iter()
+        .filter(|(k, _)| !new_keys.contains_key(*k) && !relinked_from.contains(k))
+        .map(|(_, v)| v)
+        .collect();
+    let added: BTreeSet<&String> = new_keys
+        .iter()
+        .filter(|(k, _)| !old_keys.contains_key(*k) && !relinked_to.contains(k))
+        .map(|(_, v)| v)
+        .collect();
+    counts.media += removed.len() + added.len() + relinked.len();
+    let mut lines: Vec<String> = removed.into_iter().map(|m| format!("  - {}", m)).collect();
+    lines.extend(added.into_iter().map(|m| format!("  + {}", m)));
+    lines.extend(
+        relinked
+            .iter()
+            .map(|(o, n)| format!("  ~ {} relinked to {}", old_keys[o], new_keys[n])),
+    );
+    lines
+}
+
+/// Master clips renamed in the project panel
+fn diff_clip_names(old: &Project, new: &Project, counts: &mut Counts) -> Vec<String> {
+    let new_by_uid: HashMap<&str, &str> = new.master_clips.iter().map(|m| (m.uid.as_str(), m.name.as_str())).collect();
+    let mut lines = Vec::new();
+    for master in &old.master_clips {
+        match new_by_uid.get(master.uid.as_str()) {
+            Some(name) if *name != master.name => {
+                counts.renames += 1;
+                lines.push(format!("  ~ \"{}\" renamed to \"{}\"", master.name, name));
+            }
+            _ => {}
+        }
+    }
+    lines.sort();
+    lines
+}
+
+fn marker_label(seq: &Sequence, marker: &Marker) -> String {
+    let mut label = format!("marker at {}", timecode(seq, marker.start));
+    if !marker.name.is_empty() {
+        label.push_str(&format!(" \"{}\"", marker.name));
+    }
+    if !marker.comment.is_empty() {
+        label.push_str(&format!(" ({})", marker.comment));
+    }
+    label
+}
+
+fn diff_markers(old_seq: &Sequence, new_seq: &Sequence, counts: &mut Counts) -> Vec<String> {
+    let key = |m: &Marker| (m.start, m.duration, m.name.clone(), m.comment.clone(), m.kind.clone());
+    let old_keys: HashSet<_> = old_seq.markers.iter().map(key).collect();
+    let new_keys: HashSet<_> = new_seq.markers.iter().map(key).collect();
+    let mut lines = Vec::new();
+    for marker in old_seq.markers.iter().filter(|m| !new_keys.contains(&key(m))) {
+        counts.markers += 1;
+        lines.push(format!("    - {}", marker_label(old_seq, marker)));
+    }
+    for marker in new_seq.markers.iter().filter(|m| !old_keys.contains(&key(m))) {
+        counts.markers += 1;
+        lines.push(format!("    + {}", marker_label(new_seq, marker)));
+    }
+    lines
+}
+
+/// How a clip on the old track was paired with one on the new track
+#[derive(Clone, Copy)]
+enum Pass {
+    /// Same source and timing (only the name may differ)
+    Same,
+    /// Same source range at another timeline position
+    Moved,
+    /// Same source and start, other length or source range
+    Trimmed,
+    /// Same timeline start, other source (e.g. Replace Footage)
+    Replaced,
+}
+
+type ItemKey = (String, i64, i64, Option<i64>, Option<i64>);
+
+impl Pass {
+    const ALL: [Pass; 4] = [Pass::Same, Pass::Moved, Pass::Trimmed, Pass::Replaced];
+
+    fn key(self, project: &Project, item: &TrackItem) -> ItemKey {
+        match self {
+            Pass::Same => (source_key(project, item), item.start, item.end, item.in_point, item.out_point),
+            Pass::Moved => (source_key(project, item), 0, 0, item.in_point, item.out_point),
+            Pass::Trimmed => (source_key(project, item), item.start, 0, None, None),
+            Pass::Replaced => (String::new(), item.start, 0, None, None),
+        }
+    }
+}
+
+/// Clip changes on one track. Items are paired pass by pass (see `Pass`); whatever
+/// is left over was added or removed.
+fn diff_track(
+    label: &str,
+    (old, old_seq, old_track): (&Project, &Sequence, &Track),
+    (new, new_seq, new_track): (&Project, &Sequence, &Track),
+    counts: &mut Counts,
+) -> Vec<String> {
+    let mut old_left: Vec<&TrackItem> = old_track.items.iter().collect();
+    let mut new_left: Vec<&TrackItem> = new_track.items.iter().collect();
+    let mut lines = Vec::new();
+    for pass in Pass::ALL {
+        let mut unmatched = Vec::new();
+        for o in old_left {
+            let Some(pos) = new_left.iter().position(|n| pass.key(new, n) == pass.key(old, o)) else {
+                unmatched.push(o);
+                continue;
+            };
+            let n = new_left.remove(pos);
+            let at = timecode(new_seq, n.start);
+            let change = match pass {
+                Pass::Same => None,
+                Pass::Moved => Some(format!("\"{}\" moved from {} to {}", n.name, timecode(old_seq, o.start), at)),
+                Pass::Trimmed => Some(format!(
+                    "\"{}\" at {} trimmed: {} -> {} long",
+                    n.name,
+                    at,
+                    timecode(old_seq, o.end - o.start),
+                    timecode(new_seq, n.end - n.start)
+                )),
+                Pass::Replaced => Some(format!("clip at {} replaced: \"{}\" -> \"{}\"", at, o.name, n.name)),
+            };
+            if let Some(change) = change {
+                counts.clips += 1;
+                lines.push(format!("    ~ {} {}", label, change));
+            }
+            if n.name != o.name && !matches!(pass, Pass::Replaced) {
+                counts.renames += 1;
+                lines.push(format!("    ~ {} clip at {} renamed \"{}\" -> \"{}\"", label, at, o.name, n.name));
+            }
+        }
+        old_left = unmatched;
+    }
+    for item in old_left {
+        counts.clips += 1;
+        lines.push(format!("    - {} \"{}\" at {}", label, item.name, timecode(old_seq, item.start)));
+    }
+    for item in new_left {
+        counts.clips += 1;
+        lines.push(format!("    + {} \"{}\" at {}", label, item.name, timecode(new_seq, item.start)));
+    }
+    lines
+}
+
+fn diff_sequence(old: &Project, old_seq: &Sequence, new: &Project, new_seq: &Sequence, counts: &mut Counts) -> Vec<String> {
+    let mut lines = Vec::new();
+    if old_seq.name != new_seq.name {
+        counts.renames += 1;
+        lines.push(format!("    ~ renamed from \"{}\"", old_seq.name));
+    }
+    let empty = Track::default();
+    for (kind, old_tracks, new_tracks) in [
+        ('V', &old_seq.video_tracks, &new_seq.video_tracks),
+        ('A', &old_seq.audio_tracks, &new_seq.audio_tracks),
+    ] {
+        if old_tracks.len() != new_tracks.len() {
+            counts.tracks += old_tracks.len().abs_diff(new_tracks.len());
+            lines.push(format!("    ~ {} {} tracks -> {}", old_tracks.len(), kind, new_tracks.len()));
+        }
+        for i in 0..old_tracks.len().max(new_tracks.len()) {
+            let label = format!("{}{}", kind, i + 1);
+            let old_track = old_tracks.get(i).unwrap_or(&empty);
+            let new_track = new_tracks.get(i).unwrap_or(&empty);
+            lines.extend(diff_track(&label, (old, old_seq, old_track), (new, new_seq, new_track), counts));
+        }
+    }
+    lines.extend(diff_markers(old_seq, new_seq, counts));
+    lines
+}
+
+fn print_section(title: &str, lines: &[String]) {
+    if !lines.is_empty() {
+        println!("\n{}", title);
+        for line in lines {
+            println!("{}", line);
+        }
+    }
+}
+
+/// Run the `diff` subcommand; returns the process exit code
+/// (0 when the projects are the same, 1 when they differ, 2 on errors)
+pub fn run(args: &DiffArgs) -> i32 {
+    let load = |path: &PathBuf| -> io::Result<Project> {
+        Project::load(path, None).map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path.display(), e)))
+    };
+    let (old, new) = match (load(&args.old), load(&args.new)) {
+        (Ok(old), Ok(new)) => (old, new),
+        (Err(e), _) | (_, Err(e)) => {
+            eprintln!("Error: cannot read {}", e);
+            return 2;
+        }
+    };
+
+    println!("Comparing {}\n       to {}", args.old.display(), args.new.display());
+    let mut counts = Counts::default();
+
+    print_section("Media:", &diff_media(&old, &new, &mut counts));
+
+    let (pairs, removed, added) = pair_by_uid_then_name(&old.sequences, &new.sequences, |s| &s.uid, |s| &s.name);
+    let mut sequence_lines = Vec::new();
+    for i in removed {
+        counts.sequences += 1;
+        sequence_lines.push(format!("  - \"{}\"", old.sequences[i].name));
+    }
+    for j in added {
+        counts.sequences += 1;
+        sequence_lines.push(format!("  + \"{}\"", new.sequences[j].name));
+    }
+    print_section("Sequences:", &sequence_lines);
+
+    for (i, j) in pairs {
+        let lines = diff_sequence(&old, &old.sequences[i], &new, &new.sequences[j], &mut counts);
+        print_section(&format!("Sequence \"{}\":", new.sequences[j].name), &lines);
+    }
+
+    print_section("Clip names:", &diff_clip_names(&old, &new, &mut counts));
+
+    println!("\n{}", "=".repeat(60));
+    if counts.total() == 0 {
+        println!("No structural differences");
+    } else {
+        println!("Media added, removed or relinked: {}", counts.media);
+        println!("Sequences added or removed: {}", counts.sequences);
+        println!("Tracks added or removed: {}", counts.tracks);
+        println!("Clip changes on tracks: {}", counts.clips);
+        println!("Marker changes: {}", counts.markers);
+        println!("Renames: {}", counts.renames);
+    }
+    println!("{}", "=".repeat(60));
+
+    if counts.total() == 0 {
+        0
+    } else {
+        1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::{Media, TICKS_PER_SECOND};
+
+    /// Ticks per frame at 25 fps
+    const FRAME: i64 = TICKS_PER_SECOND / 25;
+
+    fn project(paths: &[(&str, &str)]) -> Project {
+        let media = paths
+            .iter()
+            .map(|(uid, path)| Media {
+                uid: uid.to_string(),
+                file_path: Some(path.to_string()),
+                ..Default::default()
+            })
+            .collect();
+        Project { media, ..Default::default() }
+    }
+
+    fn sequence() -> Sequence {
+        Sequence { frame_rate: Some(FRAME), ..Default::default() }
+    }
+
+    /// A clip of `media` at timeline frame `start`, `len` frames long, from source frame `from`
+    fn clip(name: &str, media: usize, start: i64, len: i64, from: i64) -> TrackItem {
+        TrackItem {
+            name: name.to_string(),
+            start: start * FRAME,
+            end: (start + len) * FRAME,
+            in_point: Some(from * FRAME),
+            out_point: Some((from + len) * FRAME),
+            source: Some(ClipSource::Media(media)),
+        }
+    }
+
+    fn track_changes(old: Vec<TrackItem>, new: Vec<TrackItem>) -> (Vec<String>, Counts) {
+        let media = project(&[("1", "D:\\Shoot\\A001.mov"), ("2", "D:\\Shoot\\A002.mov")]);
+        let seq = sequence();
+        let (old, new) = (Track { items: old }, Track { items: new });
+        let mut counts = Counts::default();
+        let lines = diff_track("V1", (&media, &seq, &old), (&media, &seq, &new), &mut counts);
+        (lines, counts)
+    }
+
+    #[test]
+    fn pairs_by_uid_before_name() {
+        let old = [("u1", "Edit"), ("", "Selects"), ("u3", "Stringout"), ("", "Selects")];
+        let new = [("", "Selects"), ("u3", "Stringout v2"), ("u1", "Edit"), ("u4", "Titles")];
+        let (pairs, removed, added) = pair_by_uid_then_name(&old, &new, |s| s.0, |s| s.1);
+        assert_eq!(pairs, vec![(0, 2), (2, 1), (1, 0)]);
+        // Only one of the two same-named sequences is still there
+        assert_eq!(removed, vec![3]);
+        assert_eq!(added, vec![3]);
+
+        // An empty UID never pairs with another empty UID
+        let old = [("", "Edit")];
+        let new = [("", "Edit v2")];
+        let (pairs, removed, added) = pair_by_uid_then_name(&old, &new, |s| s.0, |s| s.1);
+        assert!(pairs.is_empty());
+        assert_eq!((removed, added), (vec![0], vec![0]));
+    }
+
+    #[test]
+    fn unchanged_and_renamed_clips() {
+        let (lines, counts) = track_changes(vec![clip("A001", 0, 0, 50, 0)], vec![clip("A001", 0, 0, 50, 0)]);
+        assert!(lines.is_empty());
+        assert_eq!(counts.total(), 0);
+
+        let (lines, counts) = track_changes(vec![clip("A001", 0, 0, 50, 0)], vec![clip("Interview", 0, 0, 50, 0)]);
+        assert_eq!(lines, vec!["    ~ V1 clip at 00:00:00:00 renamed \"A001\" -> \"Interview\""]);
+        assert_eq!((counts.clips, counts.renames), (0, 1));
+    }
+
+    #[test]
+    fn moved_trimmed_and_replaced_clips() {
+        let (lines, _) = track_changes(vec![clip("A001", 0, 0, 50, 10)], vec![clip("A001", 0, 100, 50, 10)]);
+        assert_eq!(lines, vec!["    ~ V1 \"A001\" moved from 00:00:00:00 to 00:00:04:00"]);
+
+        let (lines, _) = track_changes(vec![clip("A001", 0, 25, 50, 10)], vec![clip("A001", 0, 25, 75, 10)]);
+        assert_eq!(lines, vec!["    ~ V1 \"A001\" at 00:00:01:00 trimmed: 00:00:02:00 -> 00:00:03:00 long"]);
+
+        let (lines, counts) = track_changes(vec![clip("A001", 0, 25, 50, 10)], vec![clip("A002", 1, 25, 50, 10)]);
+        assert_eq!(lines, vec!["    ~ V1 clip at 00:00:01:00 replaced: \"A001\" -> \"A002\""]);
+        // The new name comes with the new source and is not a rename
+        assert_eq!((counts.clips, counts.renames), (1, 0));
+    }
+
+    #[test]
+    fn added_and_removed_clips() {
+        let (lines, counts) = track_changes(
+            vec![clip("A001", 0, 0, 50, 0), clip("A002", 1, 50, 50, 0)],
+            vec![clip("A001", 0, 0, 50, 0), clip("A002", 1, 75, 25, 300)],
+        );
+        assert_eq!(lines, vec!["    - V1 \"A002\" at 00:00:02:00", "    + V1 \"A002\" at 00:00:03:00"]);
+        assert_eq!(counts.clips, 2);
+    }
+
+    #[test]
+    fn same_named_clips_are_paired_by_source_and_timing() {
+        // Two cuts from one shot; only the second one moved
+        let (lines, counts) = track_changes(
+            vec![clip("A001", 0, 0, 50, 0), clip("A001", 0, 100, 50, 200)],
+            vec![clip("A001", 0, 200, 50, 200), clip("A001", 0, 0, 50, 0)],
+        );
+        assert_eq!(lines, vec!["    ~ V1 \"A001\" moved from 00:00:04:00 to 00:00:08:00"]);
+        assert_eq!(counts.total(), 1);
+
+        // The first of two identical-looking cuts was deleted
+        let (lines, _) = track_changes(
+            vec![clip("A001", 0, 0, 50, 0), clip("A001", 0, 50, 50, 50)],
+            vec![clip("A001", 0, 50, 50, 50)],
+        );
+        assert_eq!(lines, vec!["    - V1 \"A001\" at 00:00:00:00"]);
+
+        // Both trimmed at the head of their own slot
+        let (lines, _) = track_changes(
+            vec![clip("A001", 0, 0, 50, 0), clip("A001", 0, 50, 50, 50)],
+            vec![clip("A001", 0, 0, 40, 0), clip("A001", 0, 50, 30, 50)],
+        );
+        assert_eq!(
+            lines,
+            vec![
+                "    ~ V1 \"A001\" at 00:00:00:00 trimmed: 00:00:02:00 -> 00:00:01:15 long",
+                "    ~ V1 \"A001\" at 00:00:02:00 trimmed: 00:00:02:00 -> 00:00:01:05 long",
+            ]
+        );
+    }
+
+    #[test]
+    fn media_added_removed_and_relinked() {
+        let old = project(&[("u1", "D:\\Shoot\\A001.mov"), ("u2", "D:\\Shoot\\A002.mov"), ("u3", "D:\\Audio\\mix.wav")]);
+        let new = project(&[("u1", "E:\\Shoot\\A001.mov"), ("u2", "d:\\shoot\\a002.MOV"), ("u4", "D:\\Audio\\mix v2.wav")]);
+        let mut counts = Counts::default();
+        assert_eq!(
+            diff_media(&old, &new, &mut counts),
+            vec![
+                "  - D:\\Audio\\mix.wav",
+                "  + D:\\Audio\\mix v2.wav",
+                "  ~ D:\\Shoot\\A001.mov relinked to E:\\Shoot\\A001.mov",
+            ]
+        );
+        assert_eq!(counts.media, 3);
+
+        // The old path is still in the project, so the new one is an addition
+        let new = project(&[("u1", "E:\\Shoot\\A001.mov"), ("u9", "D:\\Shoot\\A001.mov")]);
+        let old = project(&[("u1", "D:\\Shoot\\A001.mov")]);
+        assert_eq!(diff_media(&old, &new, &mut Counts::default()), vec!["  + E:\\Shoot\\A001.mov"]);
+    }
+}
@@ -0,0 +1,362 @@
+// File discovery: walk the search roots and hand each project file to a callback
+// as soon as it is seen. With more than one walker thread, directories (and
+// several roots) are read concurrently, which matters most on network shares
+// where each directory listing is a round trip.
+//
+// Self-contained (std, walkdir, ignore and the rules module) so the traversal
+// benchmark can include it directly.
+
+use ignore::{WalkBuilder, WalkState};
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use walkdir::WalkDir;
+
+use crate::rules::PathRules;
+
+/// Which files to report, and how many threads walk the directories
+#[derive(Clone, Debug)]
+pub struct DiscoverOptions {
+    pub follow_links: bool,
+    /// Directory names to skip (case-insensitive)
+    pub exclude_dirs: Option<Vec<String>>,
+    /// File extensions to report (case-insensitive)
+    pub extensions: Vec<String>,
+    pub min_size_bytes: Option<u64>,
+    /// Only files modified at or after this time
+    pub modified_after: Option<SystemTime>,
+    /// Only files modified before this time
+    pub modified_before: Option<SystemTime>,
+    /// Deepest level to descend to below each root (1 = files directly in the root)
+    pub max_depth: Option<usize>,
+    /// Include / exclude globs and ignore files
+    pub rules: PathRules,
+    /// 1 walks one directory at a time; 0 picks a thread count from the number of CPUs
+    pub walk_threads: usize,
+}
+
+impl DiscoverOptions {
+    fn is_excluded(&self, name: &OsStr) -> bool {
+        match (&self.exclude_dirs, name.to_str()) {
+            (Some(excludes), Some(name)) => excludes.iter().any(|exc| name.eq_ignore_ascii_case(exc)),
+            _ => false,
+        }
+    }
+
+    /// Whether the walk descends into (or, for a file, looks at) an entry.
+    /// Path rules only apply below the roots: a root is searched as given.
+    fn keeps(&self, path: &Path, name: &OsStr, is_dir: bool, depth: usize) -> bool {
+        !self.is_excluded(name) && (depth == 0 || !self.rules.is_excluded(path, is_dir))
+    }
+
+    /// Extension and include globs
+    fn is_wanted_file(&self, path: &Path) -> bool {
+        path.extension()
+            .is_some_and(|ext| self.extensions.iter().any(|e| ext.eq_ignore_ascii_case(e)))
+            && self.rules.is_included(path)
+    }
+
+    /// Minimum size and modification time filters; files whose metadata can't be read are kept.
+    /// Files over the maximum size are left to the search, which reports them as skipped.
+    fn passes_metadata(&self, metadata: Option<fs::Metadata>) -> bool {
+        let Some(metadata) = metadata else {
+            return true;
+        };
+        if self.min_size_bytes.is_some_and(|min| metadata.len() < min) {
+            return false;
+        }
+        if self.modified_after.is_none() && self.modified_before.is_none() {
+            return true;
+        }
+        match metadata.modified() {
+            Ok(modified) => {
+                self.modified_after.is_none_or(|after| modified >= after)
+                    && self.modified_before.is_none_or(|before| modified < before)
+            }
+            Err(_) => true,
+        }
+    }
+
+    /// Same filters as the walk, applied to a single path under one of `roots` (used by --watch).
+    /// A deleted file has no metadata but still counts, so its removal can be reported.
+    pub fn is_target(&self, path: &Path, roots: &[PathBuf]) -> bool {
+        let within_depth = self.max_depth.is_none_or(|max| {
+            roots
+                .iter()
+                .filter_map(|root| path.strip_prefix(root).ok())
+                .any(|rel| rel.components().count() <= max)
+        });
+        within_depth
+            && self.is_wanted_file(path)
+            && !path.components().any(|c| self.is_excluded(c.as_os_str()))
+            && !path.ancestors().skip(1).any(|dir| self.rules.is_excluded(dir, true))
+            && !self.rules.is_excluded(path, false)
+            && self.passes_metadata(fs::metadata(path).ok())
+    }
+}
+
+/// Walk `roots`, handing each matching file to `found` as soon as it is seen.
+/// Stops early on Ctrl+C or when `found` returns false. With several walker
+/// threads, `found` is called concurrently and files arrive in no fixed order.
+pub fn discover_files(
+    roots: &[PathBuf],
+    options: &DiscoverOptions,
+    interrupted: &AtomicBool,
+    found: impl Fn(PathBuf) -> bool + Sync,
+) {
+    let roots: Vec<&PathBuf> = roots
+        .iter()
+        .filter(|root| {
+            let exists = root.exists();
+            if !exists {
+                eprintln!("Warning: Path does not exist: {:?}", root);
+            }
+            exists
+        })
+        .collect();
+
+    if options.walk_threads == 1 {
+        walk_sequential(&roots, options, interrupted, found);
+    } else {
+        walk_parallel(&roots, options, interrupted, found);
+    }
+}
+
+fn walk_sequential(
+    roots: &[&PathBuf],
+    options: &DiscoverOptions,
+    interrupted: &AtomicBool,
+    found: impl Fn(PathBuf) -> bool,
+) {
+    for root in roots {
+        let mut walker = WalkDir::new(root).follow_links(options.follow_links);
+        if let Some(max_depth) = options.max_depth {
+            walker = walker.max_depth(max_depth);
+        }
+        for entry in walker
+            .into_iter()
+            .filter_entry(|e| options.keeps(e.path(), e.file_name(), e.file_type().is_dir(), e.depth()))
+            .filter_map(|e| e.ok())
+        {
+            if interrupted.load(Ordering::SeqCst) {
+                return;
+            }
+            if entry.file_type().is_file()
+                && options.is_wanted_file(entry.path())
+                && options.passes_metadata(entry.metadata().ok())
+                && !found(entry.into_path())
+            {
+                return;
+            }
+        }
+    }
+}
+
+fn walk_parallel(
+    roots: &[&PathBuf],
+    options: &DiscoverOptions,
+    interrupted: &AtomicBool,
+    found: impl Fn(PathBuf) -> bool + Sync,
+) {
+    let Some((first, rest)) = roots.split_first() else {
+        return;
+    };
+
+    // All roots share one work queue, so their directories are read side by side
+    let mut builder = WalkBuilder::new(first);
+    for root in rest {
+        builder.add(root);
+    }
+    let filter_options = Arc::new(options.clone());
+    builder
+        // No hidden-file or .gitignore rules: report exactly what the sequential walk does
+        .standard_filters(false)
+        .follow_links(options.follow_links)
+        .max_depth(options.max_depth)
+        .threads(options.walk_threads)
+        .filter_entry(move |e| {
+            let is_dir = e.file_type().is_some_and(|t| t.is_dir());
+            filter_options.keeps(e.path(), e.file_name(), is_dir, e.depth())
+        });
+
+    builder.build_parallel().run(|| {
+        Box::new(|entry| {
+            if interrupted.load(Ordering::SeqCst) {
+                return WalkState::Quit;
+            }
+            let Ok(entry) = entry else {
+                return WalkState::Continue;
+            };
+            if entry.file_type().is_some_and(|t| t.is_file())
+                && options.is_wanted_file(entry.path())
+                && options.passes_metadata(entry.metadata().ok())
+                && !found(entry.into_path())
+            {
+                return WalkState::Quit;
+            }
+            WalkState::Continue
+        })
+    });
+}
+
+/// Days since 1970-01-01 of a proleptic Gregorian date
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = (if year >= 0 { year } else { year - 399 }) / 400;
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Number of days in a month of the proleptic Gregorian calendar
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Parse a modification time filter: a date (YYYY-MM-DD, midnight UTC) or an age
+/// relative to now, such as 90d, 2w or 12h
+pub fn parse_time_filter(text: &str) -> Result<SystemTime, String> {
+    let text = text.trim();
+    let invalid = || format!("invalid date '{}': use YYYY-MM-DD or an age such as 90d, 2w or 12h", text);
+
+    if let Some(unit) = text.chars().last().filter(|c| matches!(c, 'd' | 'w' | 'h')) {
+        let count: u64 = text[..text.len() - 1].parse().map_err(|_| invalid())?;
+        let unit_secs = match unit {
+            'w' => 7 * 86_400,
+            'd' => 86_400,
+            _ => 3600,
+        };
+        return count
+            .checked_mul(unit_secs)
+            .and_then(|secs| SystemTime::now().checked_sub(Duration::from_secs(secs)))
+            .ok_or_else(invalid);
+    }
+
+    let parts: Vec<i64> = text.split('-').map(|p| p.parse().map_err(|_| invalid())).collect::<Result<_, _>>()?;
+    let [year, month, day] = parts[..] else {
+        return Err(invalid());
+    };
+    if !(1..=12).contains(&month) || !(1..=days_in_month(year, month)).contains(&day) {
+        return Err(invalid());
+    }
+    let secs = days_from_civil(year, month, day) * 86_400;
+    let time = if secs >= 0 {
+        UNIX_EPOCH.checked_add(Duration::from_secs(secs as u64))
+    } else {
+        UNIX_EPOCH.checked_sub(Duration::from_secs(secs.unsigned_abs()))
+    };
+    time.ok_or_else(invalid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn days(text: &str) -> Result<i64, String> {
+        let time = parse_time_filter(text)?;
+        Ok(match time.duration_since(UNIX_EPOCH) {
+            Ok(after) => (after.as_secs() / 86_400) as i64,
+            Err(before) => -((before.duration().as_secs() / 86_400) as i64),
+        })
+    }
+
+    #[test]
+    fn dates_are_checked_against_the_month_length() {
+        assert_eq!(days("1970-01-01"), Ok(0));
+        assert_eq!(days("1969-12-31"), Ok(-1));
+        assert_eq!(days("2024-01-01"), Ok(19_723));
+        assert_eq!(days("2024-02-29"), Ok(19_782));
+        assert_eq!(days("2024-03-01"), Ok(19_783));
+        assert_eq!(days("2000-02-29"), Ok(11_016));
+        assert!(days("2024-12-31").is_ok());
+        assert!(days(" 2024-04-30 ").is_ok());
+
+        for invalid in ["2024-02-31", "2023-04-31", "2023-02-29", "1900-02-29", "2024-06-31", "2024-13-01", "2024-00-10", "2024-01-00", "2024-01", "2024/01/01", "yesterday", ""] {
+            assert!(days(invalid).is_err(), "accepted {:?}", invalid);
+        }
+    }
+
+    #[test]
+    fn ages_count_back_from_now() {
+        for (text, secs) in [("90d", 90 * 86_400), ("2w", 14 * 86_400), ("12h", 12 * 3600), ("0d", 0)] {
+            let before = SystemTime::now();
+            let time = parse_time_filter(text).unwrap();
+            let after = SystemTime::now();
+            let expected = Duration::from_secs(secs);
+            assert!(before.duration_since(time).unwrap_or_default() <= expected, "{}", text);
+            assert!(after.duration_since(time).unwrap() >= expected, "{}", text);
+        }
+        for invalid in ["5m", "3y", "d", "-3d", "1.5d", "90 days"] {
+            let error = parse_time_filter(invalid).unwrap_err();
+            assert!(error.contains(invalid), "{}", error);
+        }
+    }
+
+    fn options(max_depth: Option<usize>) -> DiscoverOptions {
+        DiscoverOptions {
+            follow_links: false,
+            exclude_dirs: None,
+            extensions: vec!["prproj".to_string()],
+            min_size_bytes: None,
+            modified_after: None,
+            modified_before: None,
+            max_depth,
+            rules: Default::default(),
+            walk_threads: 1,
+        }
+    }
+
+    #[test]
+    fn depth_counts_levels_below_the_root() {
+        let roots = [PathBuf::from("/jobs"), PathBuf::from("/archive/2024")];
+        let shallow = options(Some(1));
+        assert!(shallow.is_target(Path::new("/jobs/edit.prproj"), &roots));
+        assert!(!shallow.is_target(Path::new("/jobs/client/edit.prproj"), &roots));
+        assert!(shallow.is_target(Path::new("/archive/2024/edit.prproj"), &roots));
+        assert!(!shallow.is_target(Path::new("/jobs/notes.txt"), &roots));
+
+        let deeper = options(Some(2));
+        assert!(deeper.is_target(Path::new("/jobs/client/edit.prproj"), &roots));
+        assert!(!deeper.is_target(Path::new("/jobs/client/old/edit.prproj"), &roots));
+        // Outside every root there is no depth to count
+        assert!(!deeper.is_target(Path::new("/elsewhere/edit.prproj"), &roots));
+        assert!(options(None).is_target(Path::new("/jobs/a/b/c/d/edit.prproj"), &roots));
+    }
+
+    #[test]
+    fn walk_and_single_paths_agree_on_depth() {
+        let root = std::env::temp_dir().join(format!("premiere-hunter-discover-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        for dir in ["", "client", "client/old", "client/old/v1"] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+            fs::write(root.join(dir).join("edit.prproj"), "").unwrap();
+        }
+        let roots = [root.clone()];
+
+        for walk_threads in [1, 2] {
+            for max_depth in [Some(1), Some(3), None] {
+                let options = DiscoverOptions { walk_threads, ..options(max_depth) };
+                let found = std::sync::Mutex::new(Vec::new());
+                discover_files(&roots, &options, &AtomicBool::new(false), |path| {
+                    found.lock().unwrap().push(path);
+                    true
+                });
+                let found = found.into_inner().unwrap();
+                assert_eq!(found.len(), max_depth.unwrap_or(4), "{:?} with {} threads", max_depth, walk_threads);
+                assert!(found.iter().all(|path| options.is_target(path, &roots)));
+            }
+        }
+        assert!(!options(Some(3)).is_target(&root.join("client/old/v1/edit.prproj"), &roots));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,393 @@
+// Timeline export (`export-edl` subcommand): write one track of a sequence as a
+// CMX3600 edit decision list, for conform and color houses that still work from
+// EDLs. Only cuts are written, and timecodes are non-drop-frame. Record times are
+// counted at the sequence rate from --record-start; source times are counted at
+// the media's own video rate from its start timecode, so they match the timecode
+// embedded in camera files.
+
+use clap::{Args, ValueEnum};
+use std::fmt::Write as _;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::media::file_name_of;
+use crate::media_asset_path;
+use crate::project::{frames_per_second, ticks_to_timecode, ClipSource, Media, Project, Sequence, Track};
+
+/// CMX3600 numbers events with three digits
+const MAX_EVENTS: usize = 999;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ReelNames {
+    /// The source file name without extension, cut to the 8 characters CMX3600 allows
+    File,
+    /// "AX" for every event; the clip name and source file comments identify the media
+    Ax,
+}
+
+#[derive(Args, Debug)]
+pub struct EdlArgs {
+    /// Project to read
+    #[arg(value_name = "PROJECT")]
+    pub project: PathBuf,
+
+    /// Sequence to export (case-insensitive; may be left out when the project has one sequence)
+    #[arg(long, value_name = "NAME")]
+    pub sequence: Option<String>,
+
+    /// Track to export: V1, V2, ... or A1, A2, ...
+    #[arg(long, value_name = "TRACK", default_value = "V1")]
+    pub track: String,
+
+    /// Timecode of the first frame of the sequence
+    #[arg(long, value_name = "HH:MM:SS:FF", default_value = "00:00:00:00")]
+    pub record_start: String,
+
+    /// How reel names are written
+    #[arg(long, value_enum, default_value_t = ReelNames::File)]
+    pub reel: ReelNames,
+
+    /// File to write the EDL to (defaults to standard output)
+    #[arg(short, long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+}
+
+/// Parse "V1" / "a2" into (is_video, zero-based index)
+fn parse_track(text: &str) -> Result<(bool, usize), String> {
+    let invalid = || format!("invalid track '{}': use V1, V2, ... or A1, A2, ...", text);
+    let (kind, number) = text.trim().split_at_checked(1).ok_or_else(invalid)?;
+    let number: usize = number.parse().ok().filter(|n| *n >= 1).ok_or_else(invalid)?;
+    match kind.to_ascii_uppercase().as_str() {
+        "V" => Ok((true, number - 1)),
+        "A" => Ok((false, number - 1)),
+        _ => Err(invalid()),
+    }
+}
+
+/// Frame count of an HH:MM:SS:FF timecode at `fps` frames per second
+fn parse_timecode(text: &str, fps: i64) -> Result<i64, String> {
+    let invalid = || format!("invalid timecode '{}': use HH:MM:SS:FF", text);
+    let parts: Vec<i64> = text
+        .trim()
+        .split([':', ';'])
+        .map(|p| p.parse().map_err(|_| invalid()))
+        .collect::<Result<_, _>>()?;
+    let [hh, mm, ss, ff] = parts[..] else {
+        return Err(invalid());
+    };
+    if hh < 0 || !(0..60).contains(&mm) || !(0..60).contains(&ss) || !(0..fps).contains(&ff) {
+        return Err(invalid());
+    }
+    Ok(((hh * 60 + mm) * 60 + ss) * fps + ff)
+}
+
+fn find_sequence<'a>(project: &'a Project, name: Option<&str>) -> Result<&'a Sequence, String> {
+    let names = || {
+        project
+            .sequences
+            .iter()
+            .map(|s| format!("\"{}\"", s.name))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    match name {
+        Some(name) => project
+            .sequences
+            .iter()
+            .find(|s| s.name.eq_ignore_ascii_case(name))
+            .ok_or_else(|| format!("no sequence named \"{}\" (sequences: {})", name, names())),
+        None if project.sequences.len() == 1 => Ok(&project.sequences[0]),
+        None if project.sequences.is_empty() => Err("the project has no sequences".to_string()),
+        None => Err(format!("the project has several sequences; choose one with --sequence ({})", names())),
+    }
+}
+
+/// Media file path, when the media is a file
+fn source_file(media: &Media) -> Option<String> {
+    media
+        .actual_media_file_path
+        .as_deref()
+        .or(media.file_path.as_deref())
+        .and_then(media_asset_path)
+}
+
+fn reel_name(reels: ReelNames, file: Option<&str>) -> String {
+    let stem = match (reels, file) {
+        (ReelNames::File, Some(file)) => {
+            let name = file_name_of(file);
+            name.rsplit_once('.').map_or(name, |(stem, _)| stem)
+        }
+        _ => "",
+    };
+    let reel: String = stem
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '_')
+        .take(8)
+        .collect::<String>()
+        .to_ascii_uppercase();
+    if reel.is_empty() {
+        "AX".to_string()
+    } else {
+        reel
+    }
+}
+
+/// CMX3600 channel field for a track
+fn channel(is_video: bool, index: usize) -> String {
+    match (is_video, index) {
+        (true, _) => "V".to_string(),
+        (false, 0) => "A".to_string(),
+        (false, n) => format!("A{}", n + 1),
+    }
+}
+
+/// The EDL text for one track, and how many events it has
+fn write_edl(
+    project: &Project,
+    seq: &Sequence,
+    track: &Track,
+    channel: &str,
+    record_start: i64,
+    reels: ReelNames,
+) -> (String, usize) {
+    let rate = seq.frame_rate.unwrap_or(0);
+    let tc = |ticks: i64| ticks_to_timecode(ticks, rate);
+    let mut edl = String::new();
+    let _ = writeln!(edl, "TITLE: {}", seq.name);
+    let _ = writeln!(edl, "FCM: NON-DROP FRAME");
+
+    let mut events = 0;
+    for item in track.items.iter().filter(|i| i.end > i.start) {
+        events += 1;
+        let media = project.media_for(item.source);
+        let file = media.and_then(source_file);
+        // In points count from the first frame of the media, whose timecode is its
+        // start; the source range is as long as the record range (speed changes
+        // are not written)
+        let source_rate = media
+            .and_then(|m| m.video.as_ref())
+            .and_then(|v| v.frame_rate)
+            .filter(|r| *r > 0)
+            .unwrap_or(rate);
+        let source_tc = |ticks: i64| ticks_to_timecode(ticks, source_rate);
+        let source_in = media.and_then(|m| m.start).unwrap_or(0) + item.in_point.unwrap_or(0);
+        let source_out = source_in + (item.end - item.start);
+        let _ = writeln!(
+            edl,
+            "\n{:03}  {:<8} {:<5} C        {} {} {} {}",
+            events,
+            reel_name(reels, file.as_deref()),
+            channel,
+            source_tc(source_in),
+            source_tc(source_out),
+            tc(record_start + item.start),
+            tc(record_start + item.end)
+        );
+        if !item.name.is_empty() {
+            let _ = writeln!(edl, "* FROM CLIP NAME: {}", item.name);
+        }
+        match (file, item.source) {
+            (Some(file), _) => {
+                let _ = writeln!(edl, "* SOURCE FILE: {}", file);
+            }
+            (None, Some(ClipSource::Sequence(i))) => {
+                let _ = writeln!(edl, "* SOURCE SEQUENCE: {}", project.sequences[i].name);
+            }
+            (None, _) => {}
+        }
+    }
+    (edl, events)
+}
+
+/// Run the `export-edl` subcommand; returns the process exit code
+pub fn run(args: &EdlArgs) -> i32 {
+    match export(args) {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            1
+        }
+    }
+}
+
+fn export(args: &EdlArgs) -> Result<(), String> {
+    let (is_video, index) = parse_track(&args.track)?;
+    let project = Project::load(&args.project, None).map_err(|e| format!("cannot read {}: {}", args.project.display(), e))?;
+    let seq = find_sequence(&project, args.sequence.as_deref())?;
+    let rate = seq
+        .frame_rate
+        .filter(|r| *r > 0)
+        .ok_or_else(|| format!("sequence \"{}\" has no video frame rate", seq.name))?;
+
+    let tracks = if is_video { &seq.video_tracks } else { &seq.audio_tracks };
+    let track = tracks.get(index).ok_or_else(|| {
+        format!(
+            "sequence \"{}\" has no track {} ({} video / {} audio tracks)",
+            seq.name,
+            args.track.to_ascii_uppercase(),
+            seq.video_tracks.len(),
+            seq.audio_tracks.len()
+        )
+    })?;
+
+    // Nominal rate, as ticks_to_timecode counts frames
+    let fps = (frames_per_second(rate).round() as i64).max(1);
+    let record_start = parse_timecode(&args.record_start, fps)? * rate;
+    let (edl, events) = write_edl(&project, seq, track, &channel(is_video, index), record_start, args.reel);
+    if events > MAX_EVENTS {
+        eprintln!("Warning: {} events, more than the {} a CMX3600 list can number", events, MAX_EVENTS);
+    }
+
+    match &args.output {
+        Some(path) => {
+            fs::write(path, edl).map_err(|e| format!("cannot write {}: {}", path.display(), e))?;
+            println!(
+                "Wrote {} {} from \"{}\" {} to {}",
+                events,
+                if events == 1 { "event" } else { "events" },
+                seq.name,
+                args.track.to_ascii_uppercase(),
+                path.display()
+            );
+        }
+        None => print!("{}", edl),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::{StreamInfo, TrackItem, TICKS_PER_SECOND};
+
+    /// Ticks per frame at 24000/1001 and 30000/1001 frames per second
+    const TPF_23_976: i64 = 10_594_584_000;
+    const TPF_29_97: i64 = 8_475_667_200;
+
+    #[test]
+    fn timecode_parsing() {
+        assert_eq!(parse_timecode("01:00:00:00", 24), Ok(86_400));
+        assert_eq!(parse_timecode("00:00:01:29", 30), Ok(59));
+        assert_eq!(parse_timecode("00;00;01;29", 30), Ok(59));
+        assert!(parse_timecode("00:00:01:24", 24).is_err());
+        assert!(parse_timecode("00:60:00:00", 24).is_err());
+        assert!(parse_timecode("01:00:00", 24).is_err());
+    }
+
+    #[test]
+    fn fractional_rates_count_nominal_frames() {
+        assert_eq!(ticks_to_timecode(24 * TPF_23_976, TPF_23_976), "00:00:01:00");
+        assert_eq!(ticks_to_timecode(86_399 * TPF_23_976, TPF_23_976), "00:59:59:23");
+        assert_eq!(ticks_to_timecode(1_799 * TPF_29_97, TPF_29_97), "00:00:59:29");
+        assert_eq!(ticks_to_timecode(1_800 * TPF_29_97, TPF_29_97), "00:01:00:00");
+        // Part of a frame is not a frame yet
+        assert_eq!(ticks_to_timecode(30 * TPF_29_97 - 1, TPF_29_97), "00:00:00:29");
+    }
+
+    #[test]
+    fn record_start_round_trips() {
+        for (tpf, fps) in [(TPF_23_976, 24), (TPF_29_97, 30)] {
+            let record_start = parse_timecode("01:00:00:00", fps).unwrap() * tpf;
+            assert_eq!(ticks_to_timecode(record_start, tpf), "01:00:00:00");
+            assert_eq!(ticks_to_timecode(record_start + 10 * tpf, tpf), "01:00:00:10");
+        }
+    }
+
+    #[test]
+    fn reel_names() {
+        assert_eq!(reel_name(ReelNames::File, Some("D:\\Shoot\\A001_C002-x.mov")), "A001_C00");
+        assert_eq!(reel_name(ReelNames::File, Some("\\Volumes\\shoot\\b cam.mxf")), "BCAM");
+        assert_eq!(reel_name(ReelNames::File, Some("D:\\Shoot\\-.mov")), "AX");
+        assert_eq!(reel_name(ReelNames::File, None), "AX");
+        assert_eq!(reel_name(ReelNames::Ax, Some("D:\\Shoot\\A001.mov")), "AX");
+    }
+
+    #[test]
+    fn tracks() {
+        assert_eq!(parse_track("V1"), Ok((true, 0)));
+        assert_eq!(parse_track("a2"), Ok((false, 1)));
+        assert!(parse_track("V0").is_err());
+        assert!(parse_track("X1").is_err());
+        assert_eq!(channel(false, 1), "A2");
+    }
+
+    #[test]
+    fn events_are_offset_by_record_start() {
+        let project = Project {
+            media: vec![Media {
+                title: "A001.mov".to_string(),
+                file_path: Some("D:\\Shoot\\A001.mov".to_string()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let seq = Sequence {
+            name: "Cut 1".to_string(),
+            frame_rate: Some(TPF_29_97),
+            ..Default::default()
+        };
+        let track = Track {
+            items: vec![TrackItem {
+                name: "Interview".to_string(),
+                start: 45 * TPF_29_97,
+                end: 75 * TPF_29_97,
+                in_point: Some(1_800 * TPF_29_97),
+                out_point: Some(1_830 * TPF_29_97),
+                source: Some(ClipSource::Media(0)),
+            }],
+        };
+        let record_start = parse_timecode("01:00:00:00", 30).unwrap() * TPF_29_97;
+        let (edl, events) = write_edl(&project, &seq, &track, "V", record_start, ReelNames::File);
+        assert_eq!(events, 1);
+        assert_eq!(
+            edl,
+            "TITLE: Cut 1\nFCM: NON-DROP FRAME\n\n\
+             001  A001     V     C        00:01:00:00 00:01:01:00 01:00:01:15 01:00:02:15\n\
+             * FROM CLIP NAME: Interview\n\
+             * SOURCE FILE: D:\\Shoot\\A001.mov\n"
+        );
+    }
+    #[test]
+    fn source_timecode_counts_from_the_media_start_at_the_media_rate() {
+        let second = TICKS_PER_SECOND;
+        let project = Project {
+            media: vec![
+                Media {
+                    file_path: Some("D:\\Shoot\\A001.mov".to_string()),
+                    start: Some(10 * 3600 * second),
+                    video: Some(StreamInfo { frame_rate: Some(second / 25), duration: None }),
+                    ..Default::default()
+                },
+                Media {
+                    file_path: Some("D:\\Sound\\T001.wav".to_string()),
+                    start: Some(108_000 * TPF_29_97),
+                    audio: Some(StreamInfo { frame_rate: Some(second / 48_000), duration: None }),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+        let seq = Sequence {
+            name: "Cut 1".to_string(),
+            frame_rate: Some(TPF_29_97),
+            ..Default::default()
+        };
+        let item = |source, in_point| TrackItem {
+            name: String::new(),
+            start: 0,
+            end: 30 * TPF_29_97,
+            in_point: Some(in_point),
+            out_point: None,
+            source: Some(ClipSource::Media(source)),
+        };
+        let track = Track { items: vec![item(0, 2 * second)] };
+        let (edl, _) = write_edl(&project, &seq, &track, "V", 0, ReelNames::Ax);
+        // 25 fps source: 10:00:02:00 plus 1.001 s of record time
+        assert!(edl.contains("V     C        10:00:02:00 10:00:03:00 00:00:00:00 00:00:01:00\n"), "{}", edl);
+
+        // Audio-only media has no frame rate of its own and counts at the sequence rate
+        let track = Track { items: vec![item(1, 0)] };
+        let (edl, _) = write_edl(&project, &seq, &track, "A", 0, ReelNames::Ax);
+        assert!(edl.contains("A     C        01:00:00:00 01:00:01:00 00:00:00:00 00:00:01:00\n"), "{}", edl);
+    }
+}
@@ -0,0 +1,199 @@
+// Per-file failure reasons: every project that can't be searched is reported with
+// a category, so corrupt projects can be told apart from access problems
+// (--verbose-errors, and the `category` of error records in machine formats).
+
+use serde::Serialize;
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCategory {
+    PermissionDenied,
+    /// A gzip-compressed project that ends early or does not decompress
+    TruncatedGzip,
+    InvalidUtf8,
+    MalformedXml,
+    /// Larger than max_file_size_mb, so not read
+    Oversized,
+    /// Anything else (file vanished, network share dropped, ...)
+    Other,
+}
+
+impl ErrorCategory {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCategory::PermissionDenied => "permission_denied",
+            ErrorCategory::TruncatedGzip => "truncated_gzip",
+            ErrorCategory::InvalidUtf8 => "invalid_utf8",
+            ErrorCategory::MalformedXml => "malformed_xml",
+            ErrorCategory::Oversized => "oversized",
+            ErrorCategory::Other => "other",
+        }
+    }
+
+    pub fn describe(&self) -> &'static str {
+        match self {
+            ErrorCategory::PermissionDenied => "permission denied",
+            ErrorCategory::TruncatedGzip => "truncated or corrupt gzip",
+            ErrorCategory::InvalidUtf8 => "invalid UTF-8",
+            ErrorCategory::MalformedXml => "malformed XML",
+            ErrorCategory::Oversized => "oversized file skipped",
+            ErrorCategory::Other => "other errors",
+        }
+    }
+}
+
+/// A project over the size limit
+#[derive(Debug)]
+pub struct FileTooLarge {
+    pub size: u64,
+    pub limit: u64,
+}
+
+impl fmt::Display for FileTooLarge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mb = |bytes: u64| bytes as f64 / (1024.0 * 1024.0);
+        write!(f, "file is {:.1} MB, over the {:.0} MB limit", mb(self.size), mb(self.limit))
+    }
+}
+
+impl Error for FileTooLarge {}
+
+/// A read error from the gzip decoder, as opposed to the file underneath it
+#[derive(Debug)]
+pub struct GzipError(pub io::Error);
+
+impl fmt::Display for GzipError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl Error for GzipError {}
+
+/// Category of an error returned while searching a project
+pub fn categorize(error: &io::Error) -> ErrorCategory {
+    if error.kind() == io::ErrorKind::PermissionDenied {
+        return ErrorCategory::PermissionDenied;
+    }
+    if let Some(inner) = error.get_ref() {
+        return categorize_inner(inner);
+    }
+    // std's line readers report bad UTF-8 as a bare InvalidData error
+    if error.kind() == io::ErrorKind::InvalidData && error.to_string().contains("UTF-8") {
+        return ErrorCategory::InvalidUtf8;
+    }
+    ErrorCategory::Other
+}
+
+/// Category of the error wrapped inside an io::Error
+fn categorize_inner(inner: &(dyn Error + Send + Sync + 'static)) -> ErrorCategory {
+    if inner.is::<FileTooLarge>() {
+        ErrorCategory::Oversized
+    } else if inner.is::<GzipError>() {
+        ErrorCategory::TruncatedGzip
+    } else if inner.is::<std::str::Utf8Error>() || inner.is::<std::string::FromUtf8Error>() {
+        ErrorCategory::InvalidUtf8
+    } else if let Some(xml) = inner.downcast_ref::<quick_xml::Error>() {
+        match xml {
+            quick_xml::Error::Io(e) => categorize(e),
+            quick_xml::Error::NonDecodable(_) => ErrorCategory::InvalidUtf8,
+            _ => ErrorCategory::MalformedXml,
+        }
+    } else if let Some(e) = inner.downcast_ref::<io::Error>() {
+        categorize(e)
+    } else {
+        ErrorCategory::Other
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::open_project_reader;
+    use crate::project::Project;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::fs;
+    use std::io::{Read, Write};
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    /// A file holding `content` in a fresh directory, which the caller removes
+    fn project_file(name: &str, content: &[u8]) -> (PathBuf, PathBuf) {
+        let dir = std::env::temp_dir().join(format!("premiere-hunter-errors-{}-{}", name, std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("edit.prproj");
+        fs::write(&path, content).unwrap();
+        (dir, path)
+    }
+
+    fn read_error(path: &std::path::Path) -> io::Error {
+        let mut bytes = Vec::new();
+        open_project_reader(path).and_then(|mut r| r.read_to_end(&mut bytes)).unwrap_err()
+    }
+
+    #[test]
+    fn corrupt_gzip() {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all("<Project>Clair de Lune</Project>".repeat(200).as_bytes()).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let (dir, path) = project_file("gzip", &compressed[..compressed.len() / 2]);
+        assert_eq!(categorize(&read_error(&path)), ErrorCategory::TruncatedGzip);
+        fs::write(&path, b"\x1f\x8b\x08\x00not really compressed").unwrap();
+        assert_eq!(categorize(&read_error(&path)), ErrorCategory::TruncatedGzip);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn invalid_utf8() {
+        let bytes = b"caf\xe9".to_vec();
+        let mut text = String::new();
+        let bare = bytes.as_slice().read_to_string(&mut text).unwrap_err();
+        assert_eq!(categorize(&bare), ErrorCategory::InvalidUtf8);
+
+        let wrapped = io::Error::new(io::ErrorKind::InvalidData, String::from_utf8(bytes.clone()).unwrap_err());
+        assert_eq!(categorize(&wrapped), ErrorCategory::InvalidUtf8);
+
+        let utf8 = std::str::from_utf8(&bytes).unwrap_err();
+        let xml = io::Error::new(io::ErrorKind::InvalidData, quick_xml::Error::NonDecodable(Some(utf8)));
+        assert_eq!(categorize(&xml), ErrorCategory::InvalidUtf8);
+    }
+
+    #[test]
+    fn malformed_xml() {
+        let (dir, path) = project_file("xml", b"<Project><Name>Clair de Lune</Nme></Project>");
+        let error = Project::load(&path, None).unwrap_err();
+        assert_eq!(categorize(&error), ErrorCategory::MalformedXml);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn permission_denied() {
+        let denied = || io::Error::from(io::ErrorKind::PermissionDenied);
+        assert_eq!(categorize(&denied()), ErrorCategory::PermissionDenied);
+        // Also when the XML reader hit it
+        let xml = io::Error::new(io::ErrorKind::InvalidData, quick_xml::Error::Io(Arc::new(denied())));
+        assert_eq!(categorize(&xml), ErrorCategory::PermissionDenied);
+    }
+
+    #[test]
+    fn oversized() {
+        let (dir, path) = project_file("oversized", b"");
+        fs::File::options().write(true).open(&path).unwrap().set_len(3 << 20).unwrap();
+        let error = Project::load(&path, Some(2 << 20)).unwrap_err();
+        assert_eq!(categorize(&error), ErrorCategory::Oversized);
+        assert_eq!(error.to_string(), "file is 3.0 MB, over the 2 MB limit");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn anything_else() {
+        let missing = fs::File::open("/nonexistent/edit.prproj").unwrap_err();
+        assert_eq!(categorize(&missing), ErrorCategory::Other);
+        assert_eq!(categorize(&io::Error::other("network share dropped")), ErrorCategory::Other);
+    }
+}
@@ -0,0 +1,260 @@
+// Persistent incremental index (--index): one entry per project under a cache
+// directory, reused for as long as the project's size and modification time
+// are unchanged, so repeat searches only re-read projects that changed.
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::UNIX_EPOCH;
+
+use crate::query::Query;
+use crate::{extract_assets, open_project_reader};
+
+/// Bump when the entry layout or extraction rules change; older entries are rebuilt
+const INDEX_VERSION: u32 = 3;
+
+#[derive(Serialize, Deserialize)]
+pub struct IndexEntry {
+    version: u32,
+    path: PathBuf,
+    size: u64,
+    mtime_secs: u64,
+    mtime_nanos: u32,
+    /// Distinct lines of the decompressed project, in document order; None when the
+    /// project is not valid UTF-8, so searches read the project itself
+    pub lines: Option<Vec<String>>,
+    pub assets: Vec<String>,
+}
+
+impl IndexEntry {
+    /// Evaluate the query over the indexed lines, matching each term as the streaming
+    /// search does. None when the index can't give the same answer as reading the
+    /// project (a match could span lines), so the caller must read it instead.
+    pub fn matches(&self, query: &Query) -> Option<bool> {
+        let lines = self.lines.as_ref().filter(|_| query.matches_within_lines())?;
+        let mut found = vec![false; query.terms.len()];
+        let mut folded = Vec::new();
+        for line in lines {
+            folded.clear();
+            folded.extend(line.bytes().map(|b| b.to_ascii_lowercase()));
+            for (i, pattern) in query.patterns.iter().enumerate() {
+                if !found[i] && pattern.find(line.as_bytes(), &folded).is_some() {
+                    found[i] = true;
+                }
+            }
+            if let Some(result) = query.evaluate(&found, false) {
+                return Some(result);
+            }
+        }
+        query.evaluate(&found, true)
+    }
+}
+
+pub struct Index {
+    dir: PathBuf,
+    reused: AtomicUsize,
+    refreshed: AtomicUsize,
+}
+
+/// <cache dir>/premiere-hunter/index, e.g. %LOCALAPPDATA%\premiere-hunter\index on Windows
+fn default_dir() -> Option<PathBuf> {
+    dirs::cache_dir().map(|d| d.join("premiere-hunter").join("index"))
+}
+
+// FNV-1a: stable across runs and Rust versions, unlike DefaultHasher
+fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for b in bytes {
+        hash ^= *b as u64;
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    hash
+}
+
+fn modified(metadata: &fs::Metadata) -> (u64, u32) {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| (d.as_secs(), d.subsec_nanos()))
+        .unwrap_or((0, 0))
+}
+
+/// Distinct lines of the decompressed project; None when a line is not valid UTF-8
+fn extract_lines(bytes: &[u8]) -> Option<Vec<String>> {
+    let mut seen: HashSet<&[u8]> = HashSet::new();
+    let mut lines = Vec::new();
+    for line in bytes.split_inclusive(|b| *b == b'\n') {
+        let line = line.strip_suffix(b"\n").unwrap_or(line);
+        if seen.insert(line) {
+            lines.push(String::from_utf8(line.to_vec()).ok()?);
+        }
+    }
+    Some(lines)
+}
+
+fn read_entry(file: &Path) -> Option<IndexEntry> {
+    let reader = BufReader::new(GzDecoder::new(File::open(file).ok()?));
+    serde_json::from_reader(reader).ok()
+}
+
+fn write_entry(file: &Path, entry: &IndexEntry) -> Result<(), io::Error> {
+    // Write then rename so a concurrent or interrupted run never sees a partial entry
+    let tmp = file.with_extension(format!("tmp{}", std::process::id()));
+    {
+        let mut writer = GzEncoder::new(BufWriter::new(File::create(&tmp)?), Compression::fast());
+        serde_json::to_writer(&mut writer, entry)?;
+        writer.finish()?.flush()?;
+    }
+    fs::rename(&tmp, file)
+}
+
+impl Index {
+    /// Open (creating if needed) the index in `dir`, or in the user's cache directory
+    pub fn open(dir: Option<PathBuf>) -> Result<Index, io::Error> {
+        let dir = dir
+            .or_else(default_dir)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no cache directory available; use --index-dir"))?;
+        fs::create_dir_all(&dir)?;
+        Ok(Index { dir, reused: AtomicUsize::new(0), refreshed: AtomicUsize::new(0) })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// The up-to-date entry for a project, re-reading it only when its size or
+    /// modification time changed. Returns None for files over the size limit.
+    pub fn entry(&self, path: &Path, max_size_bytes: Option<usize>) -> Result<Option<IndexEntry>, io::Error> {
+        let metadata = fs::metadata(path)?;
+        if let Some(max_bytes) = max_size_bytes {
+            if metadata.len() > max_bytes as u64 {
+                return Ok(None);
+            }
+        }
+        let size = metadata.len();
+        let (mtime_secs, mtime_nanos) = modified(&metadata);
+
+        let key = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        let file = self.dir.join(format!("{:016x}.json.gz", fnv1a(key.to_string_lossy().as_bytes())));
+
+        if let Some(entry) = read_entry(&file) {
+            if entry.version == INDEX_VERSION
+                && entry.path == key
+                && entry.size == size
+                && entry.mtime_secs == mtime_secs
+                && entry.mtime_nanos == mtime_nanos
+            {
+                self.reused.fetch_add(1, Ordering::Relaxed);
+                return Ok(Some(entry));
+            }
+        }
+
+        // One decompression serves both the lines and the assets
+        let mut bytes = Vec::new();
+        BufReader::new(open_project_reader(path)?).read_to_end(&mut bytes)?;
+        let lines = extract_lines(&bytes);
+        let assets = extract_assets(&bytes);
+        let entry = IndexEntry {
+            version: INDEX_VERSION,
+            path: key,
+            size,
+            mtime_secs,
+            mtime_nanos,
+            lines,
+            assets,
+        };
+        // A failed cache write only costs a re-read next time
+        if let Err(e) = write_entry(&file, &entry) {
+            eprintln!("Warning: failed to update index entry for {:?}: {}", path, e);
+        }
+        self.refreshed.fetch_add(1, Ordering::Relaxed);
+        Ok(Some(entry))
+    }
+
+    /// (entries reused, entries rebuilt) so far
+    pub fn stats(&self) -> (usize, usize) {
+        (self.reused.load(Ordering::Relaxed), self.refreshed.load(Ordering::Relaxed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scan::stream_contains;
+
+    const PROJECT: &str = "<?xml version=\"1.0\"?>\r\n\
+<PremiereData>\r\n\
+\t<Media>\r\n\
+\t\t<FilePath>D:\\Shoot\\Clair de Lune.wav</FilePath>\r\n\
+\t\t<Title>Éclair take 2</Title>\r\n\
+\t</Media>\r\n\
+\t<Media>\r\n\
+\t\t<FilePath>D:\\Shoot\\chapter_one plane.mov</FilePath>\r\n\
+\t</Media>\r\n\
+</PremiereData>\r\n";
+
+    fn entry(bytes: &[u8]) -> IndexEntry {
+        IndexEntry {
+            version: INDEX_VERSION,
+            path: PathBuf::new(),
+            size: 0,
+            mtime_secs: 0,
+            mtime_nanos: 0,
+            lines: extract_lines(bytes),
+            assets: extract_assets(bytes),
+        }
+    }
+
+    #[test]
+    fn lines_are_distinct_and_keep_carriage_returns() {
+        let lines = extract_lines(b"a\r\nb\na\r\n\nb").unwrap();
+        assert_eq!(lines, vec!["a\r", "b", ""]);
+        assert_eq!(extract_lines(b"ok\n\xff\xfe\n"), None);
+    }
+
+    #[test]
+    fn index_agrees_with_streaming_search() {
+        let entry = entry(PROJECT.as_bytes());
+        for text in [
+            "filepath",
+            "d:\\shoot\\clair",
+            "CLAIR DE LUNE",
+            "éclair",
+            "ÉCLAIR TAKE",
+            "</Media>\r",
+            "missing",
+            "chapter AND NOT plane",
+            "clair OR missing",
+            "NOT missing",
+            "(lune OR sun) AND \"take 2\"",
+            "Media> <Media",
+        ] {
+            let query = Query::parse(text, false).unwrap();
+            let streamed = stream_contains(PROJECT.as_bytes(), &query).unwrap();
+            assert_eq!(entry.matches(&query), Some(streamed), "query {:?}", text);
+        }
+    }
+
+    #[test]
+    fn index_defers_when_it_cannot_answer() {
+        let query = Query::parse("Media>\\s*<Media", true).unwrap();
+        assert_eq!(entry(PROJECT.as_bytes()).matches(&query), None);
+        assert!(stream_contains(PROJECT.as_bytes(), &query).unwrap());
+
+        let query = Query::parse("filepath", false).unwrap();
+        assert_eq!(entry(b"<FilePath>\xff</FilePath>").matches(&query), None);
+    }
+
+    #[test]
+    fn assets_come_from_the_same_read() {
+        let entry = entry(PROJECT.as_bytes());
+        assert_eq!(entry.assets, vec!["D:\\Shoot\\Clair de Lune.wav", "D:\\Shoot\\chapter_one plane.mov"]);
+    }
+}
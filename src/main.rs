@@ -1,23 +1,72 @@
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use indicatif::{ProgressBar, ProgressStyle};
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::io::{self, BufReader, Read, Seek, SeekFrom, Write};
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicUsize, Ordering, AtomicBool};
-use std::sync::Arc;
-use std::collections::HashSet;
-use walkdir::{DirEntry, WalkDir};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::collections::{HashMap, HashSet};
 use flate2::read::GzDecoder;
 use quick_xml::events::Event;
 use quick_xml::Reader;
 
+mod autosave;
+mod collect;
+mod diff;
+mod edl;
+mod discover;
+mod errors;
+mod index;
+mod media;
+mod occurrences;
+mod output;
+mod probe;
+mod project;
+mod query;
+mod relink;
+mod remap;
+mod rules;
+mod scan;
+mod scope;
+mod search;
+mod sort;
+mod terms;
+mod uses;
+mod watch;
+
+use autosave::{AutoSaveMode, AutoSaves};
+use collect::CollectArgs;
+use diff::DiffArgs;
+use edl::EdlArgs;
+use discover::{discover_files, parse_time_filter, DiscoverOptions};
+use errors::{categorize, GzipError};
+use index::Index;
+use output::{Output, OutputFormat, Record, Summary};
+use query::Query;
+use relink::RelinkArgs;
+use remap::RemapArgs;
+use rules::PathRules;
+use scope::ScopeKind;
+use search::{Mode, Search, Tally};
+use sort::{SortKey, SortedResults};
+use terms::{load_terms_file, TermSet};
+use uses::MediaTarget;
+use watch::{record_key, watch};
+
 #[derive(Debug, Deserialize, Serialize)]
 struct Config {
     search_text: Option<String>,
+    /// Several terms checked in one pass per project; reports which ones each project contains
+    search_terms: Option<Vec<String>>,
+    /// File with one search term per line
+    terms_file: Option<PathBuf>,
     paths: Option<Vec<PathBuf>>,
     threads: Option<usize>,
+    /// Threads walking the directory tree (1 = one directory at a time; defaults to one per CPU core)
+    walk_threads: Option<usize>,
     /// When true, automatically include common drives (C:\ and D:\) in the search roots
     auto_drives: Option<bool>,
     #[serde(default = "default_extensions")]
@@ -25,7 +74,38 @@ struct Config {
     #[serde(default)]
     follow_links: bool,
     max_file_size_mb: Option<usize>,
+    /// Skip files smaller than this (in KB)
+    min_file_size_kb: Option<u64>,
+    /// Only files modified on or after this date (YYYY-MM-DD) or within this age (90d, 2w, 12h)
+    modified_after: Option<String>,
+    /// Only files modified before this date (YYYY-MM-DD) or longer ago than this age
+    modified_before: Option<String>,
+    /// How many directory levels below each search path to descend (1 = only files directly in it)
+    max_depth: Option<usize>,
     exclude_dirs: Option<Vec<String>>,
+    /// Only search files whose path matches one of these globs
+    include: Option<Vec<String>>,
+    /// Gitignore-style globs of files and directories to skip
+    exclude: Option<Vec<String>>,
+    /// Treat search_text as a regular expression instead of a plain substring
+    #[serde(default)]
+    regex: bool,
+    /// Restrict matching to the text of these element kinds (name, clip-name, marker, title, path)
+    scope: Option<Vec<ScopeKind>>,
+    /// Result format: human (default), json, ndjson or csv
+    format: Option<OutputFormat>,
+    /// Emit results once the scan ends, sorted by path, modified, size or matches (defaults to streaming)
+    sort: Option<SortKey>,
+    /// List each file that could not be searched, with the reason, before the summary
+    #[serde(default)]
+    verbose_errors: bool,
+    /// Reuse the persistent index of scanned projects
+    #[serde(default)]
+    index: bool,
+    /// Where the index is stored (defaults to the user's cache directory)
+    index_dir: Option<PathBuf>,
+    /// Group auto-save copies by project: newest or all
+    autosave: Option<AutoSaveMode>,
 }
 
 fn default_extensions() -> Vec<String> {
@@ -35,11 +115,24 @@ fn default_extensions() -> Vec<String> {
 #[derive(Parser, Debug)]
 #[command(name = "premiere-hunter")]
 #[command(about = "Fast parallel search for text in Premiere Pro project files", long_about = None)]
+#[command(args_conflicts_with_subcommands = true)]
 struct Args {
-    /// Text to search for (case-insensitive)
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Text to search for (case-insensitive). Use AND / OR / NOT (uppercase) and parentheses
+    /// to combine several terms, e.g. "clair de lune" AND "final mix" AND NOT temp
     #[arg(value_name = "SEARCH_TEXT")]
     search_text: Option<String>,
 
+    /// Search for several terms at once and report which ones each project contains (repeatable)
+    #[arg(long = "term", value_name = "TERM", conflicts_with_all = ["search_text", "regex", "scope", "all_matches", "show_snippets"])]
+    terms: Vec<String>,
+
+    /// Read search terms from a file, one per line (blank lines and lines starting with # are skipped)
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["search_text", "regex", "scope", "all_matches", "show_snippets"])]
+    terms_file: Option<PathBuf>,
+
     /// Paths to search (defaults to C:\ and D:\ on Windows)
     #[arg(short, long, value_delimiter = ',')]
     paths: Option<Vec<PathBuf>>,
@@ -53,6 +146,34 @@ struct Args {
     #[arg(short, long)]
     threads: Option<usize>,
 
+    /// Only search files whose full path matches this glob, e.g. "**/Projects/**" (repeatable)
+    #[arg(long, value_name = "GLOB")]
+    include: Vec<String>,
+
+    /// Skip files and directories matching this gitignore-style glob, e.g. "**/Adobe Premiere Pro Auto-Save/**" (repeatable)
+    #[arg(long, value_name = "GLOB")]
+    exclude: Vec<String>,
+
+    /// Only search files modified on or after this date (YYYY-MM-DD) or within this age, e.g. 90d, 2w or 12h
+    #[arg(long, value_name = "DATE")]
+    modified_after: Option<String>,
+
+    /// Only search files modified before this date (YYYY-MM-DD) or longer ago than this age
+    #[arg(long, value_name = "DATE")]
+    modified_before: Option<String>,
+
+    /// Skip files smaller than this many KB
+    #[arg(long, value_name = "KB")]
+    min_size_kb: Option<u64>,
+
+    /// How many directory levels below each search path to descend (1 = only files directly in it)
+    #[arg(long, value_name = "N")]
+    max_depth: Option<usize>,
+
+    /// Number of threads walking the directory tree, so several directories and search roots are listed at once (1 = sequential walk; defaults to number of CPU cores)
+    #[arg(long, value_name = "N")]
+    walk_threads: Option<usize>,
+
     /// Path to YAML configuration file
     #[arg(short, long)]
     config: Option<PathBuf>,
@@ -61,6 +182,18 @@ struct Args {
     #[arg(long, default_value_t = false)]
     list_assets: bool,
 
+    /// List each sequence in every .prproj: frame rate, frame size, track counts and duration. If SEARCH_TEXT is provided, it filters sequences by name.
+    #[arg(long, default_value_t = false, conflicts_with = "list_assets")]
+    list_sequences: bool,
+
+    /// Check whether each asset still exists (absolute path first, then RelativePath against the project's folder). Exits with status 1 if any media is missing. If SEARCH_TEXT is provided, it filters assets by substring (case-insensitive).
+    #[arg(long, default_value_t = false, conflicts_with_all = ["list_assets", "list_sequences"])]
+    check_media: bool,
+
+    /// Find the projects (and sequences) that use a media file, given as a full path, trailing path components or a file name
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["list_assets", "list_sequences", "check_media"])]
+    uses: Option<String>,
+
     /// Print a text snippet around each match (extracted from the project file)
     #[arg(long, default_value_t = false)]
     show_snippets: bool,
@@ -68,6 +201,99 @@ struct Args {
     /// Max number of characters to show in each snippet (total)
     #[arg(long, default_value_t = 120)]
     snippet_chars: usize,
+
+    /// Treat SEARCH_TEXT as a regular expression (case-insensitive, matched within each line of the project XML)
+    #[arg(long, default_value_t = false)]
+    regex: bool,
+
+    /// Only match inside specific XML elements instead of the raw file text (comma-separated)
+    #[arg(long, value_enum, value_delimiter = ',')]
+    scope: Option<Vec<ScopeKind>>,
+
+    /// Report every match in each project with a running count, line number and element path
+    #[arg(long, default_value_t = false)]
+    all_matches: bool,
+
+    /// Result format. Machine-readable formats go to stdout; progress and status messages go to stderr
+    #[arg(long, value_enum)]
+    format: Option<OutputFormat>,
+
+    /// Hold results until the scan ends and emit them sorted, so repeated runs give identical output (path A to Z; modified, size and matches largest first)
+    #[arg(long, value_enum, value_name = "KEY")]
+    sort: Option<SortKey>,
+
+    /// List every file that could not be searched, grouped by reason (permission denied, truncated gzip, invalid UTF-8, malformed XML, oversized), before the summary
+    #[arg(long, default_value_t = false)]
+    verbose_errors: bool,
+
+    /// Keep a persistent index of scanned projects so repeat searches only re-read changed files (plain search and --list-assets)
+    #[arg(long, default_value_t = false)]
+    index: bool,
+
+    /// Directory for the index (implies --index; defaults to the user's cache directory)
+    #[arg(long, value_name = "DIR")]
+    index_dir: Option<PathBuf>,
+
+    /// Group results from "Adobe Premiere Pro Auto-Save" copies under their project, reporting only the newest matching copy or every matching version with its timestamp
+    #[arg(long, value_enum, value_name = "MODE")]
+    autosave: Option<AutoSaveMode>,
+
+    /// After the initial scan, keep watching the search paths and re-run the search on projects as they are created, saved or removed (Ctrl+C to stop)
+    #[arg(long, default_value_t = false)]
+    watch: bool,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Write a copy of a project with media paths rewritten by prefix rules (the original is left untouched)
+    Remap(RemapArgs),
+    /// Find missing media by file name under media roots and propose (or, with --apply, write) a relink per project
+    Relink(RelinkArgs),
+    /// Copy a project and all of its media into one folder, relink the copy and write a manifest
+    Collect(CollectArgs),
+    /// Compare two versions of a project: media, sequences, clips on tracks, markers and clip names
+    Diff(DiffArgs),
+    /// Write one track of a sequence as a CMX3600 EDL
+    ExportEdl(EdlArgs),
+}
+
+// Informational output: stdout for the human report, stderr when stdout carries machine-readable results
+macro_rules! status {
+    ($human:expr, $($arg:tt)*) => {
+        if $human {
+            println!($($arg)*);
+        } else {
+            eprintln!($($arg)*);
+        }
+    };
+}
+
+/// Terms given inline plus those read from `file`; exits on an unreadable file
+fn collect_terms(terms: &[String], file: Option<&Path>) -> Vec<String> {
+    let mut all = terms.to_vec();
+    if let Some(file) = file {
+        match load_terms_file(file) {
+            Ok(from_file) => all.extend(from_file),
+            Err(e) => {
+                eprintln!("Error reading terms file {:?}: {}", file, e);
+                std::process::exit(1);
+            }
+        }
+    }
+    all
+}
+
+/// Canonical form of a search root (as given when it does not exist), without the
+/// `\\?\` prefix Windows adds to canonical paths
+fn absolute_root(path: &Path) -> PathBuf {
+    let Ok(canonical) = fs::canonicalize(path) else {
+        return path.to_path_buf();
+    };
+    match canonical.to_str().and_then(|s| s.strip_prefix(r"\\?\")) {
+        Some(rest) if !rest.starts_with("UNC") => PathBuf::from(rest),
+        Some(rest) => PathBuf::from(format!(r"\\{}", &rest[4..])),
+        None => canonical,
+    }
 }
 
 fn load_config(path: &PathBuf) -> Result<Config, Box<dyn std::error::Error>> {
@@ -76,69 +302,59 @@ fn load_config(path: &PathBuf) -> Result<Config, Box<dyn std::error::Error>> {
     Ok(config)
 }
 
-fn file_contains_case_insensitive(
-    path: &PathBuf,
-    search_text: &str,
-    max_size_bytes: Option<usize>,
-) -> Result<bool, std::io::Error> {
-    // Check file size if limit is set (on-disk size)
-    if let Some(max_bytes) = max_size_bytes {
-        let metadata = fs::metadata(path)?;
-        if metadata.len() > max_bytes as u64 {
-            return Ok(false); // Skip files that are too large
-        }
+/// Gzip decoder that marks its own read errors, so a damaged archive is reported as such
+struct GzipReader<R: Read>(GzDecoder<R>);
+
+impl<R: Read> Read for GzipReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0.read(buf).map_err(|e| {
+            // OS errors come from the file underneath and keep their own category
+            if e.kind() == io::ErrorKind::Interrupted || e.raw_os_error().is_some() {
+                e
+            } else {
+                io::Error::new(e.kind(), GzipError(e))
+            }
+        })
     }
+}
 
-    // Open file and detect gzip by magic bytes 0x1F 0x8B
+/// Open a project file for reading, transparently decompressing gzip
+/// (.prproj files are usually gzip-compressed XML, detected by magic bytes 0x1F 0x8B)
+fn open_project_reader(path: &Path) -> Result<Box<dyn Read>, std::io::Error> {
     let mut file = fs::File::open(path)?;
     let mut magic = [0u8; 2];
     let n = file.read(&mut magic)?;
     file.seek(SeekFrom::Start(0))?; // rewind after peek
 
-    let reader: Box<dyn Read> = if n == 2 && magic == [0x1F, 0x8B] {
-        Box::new(GzDecoder::new(file))
+    if n == 2 && magic == [0x1F, 0x8B] {
+        Ok(Box::new(GzipReader(GzDecoder::new(file))))
     } else {
-        Box::new(file)
-    };
-
-    let reader = BufReader::new(reader);
-
-    let search_lower = search_text.to_lowercase();
-    let search_len = search_text.len();
-
-    let mut overlap = String::new();
-
-    // Read lines as UTF-8; if an encoding error occurs, surface it so caller counts as error
-    for line in reader.lines() {
-        let line = line?;
-        let combined = format!("{}{}", overlap, line);
-
-        if combined.to_lowercase().contains(&search_lower) {
-            return Ok(true);
-        }
+        Ok(Box::new(file))
+    }
+}
 
-        // Keep overlap of last (search_len - 1) chars for matches across lines
-        if combined.len() >= search_len && search_len > 0 {
-            overlap = combined
-                .chars()
-                .rev()
-                .take(search_len - 1)
-                .collect::<String>()
-                .chars()
-                .rev()
-                .collect();
-        } else {
-            overlap = combined;
+fn file_contains_case_insensitive(
+    path: &Path,
+    query: &Query,
+    max_size_bytes: Option<usize>,
+) -> Result<bool, std::io::Error> {
+    // Check file size if limit is set (on-disk size)
+    if let Some(max_bytes) = max_size_bytes {
+        let metadata = fs::metadata(path)?;
+        if metadata.len() > max_bytes as u64 {
+            return Ok(false); // Skip files that are too large
         }
     }
 
-    Ok(false)
+    scan::stream_contains(open_project_reader(path)?, query)
 }
 
-// Streaming search that returns the first matched text snippet for display
+// Streaming search that returns the first matched text snippet for display.
+// For boolean queries the snippet comes from the first non-negated term found;
+// an empty snippet means the query matched without any positive term.
 fn file_snippet_case_insensitive(
-    path: &PathBuf,
-    search_text: &str,
+    path: &Path,
+    query: &Query,
     max_size_bytes: Option<usize>,
     snippet_chars: usize,
 ) -> Result<Option<String>, std::io::Error> {
@@ -150,79 +366,34 @@ fn file_snippet_case_insensitive(
         }
     }
 
-    // Open file and detect gzip by magic bytes 0x1F 0x8B
-    let mut file = fs::File::open(path)?;
-    let mut magic = [0u8; 2];
-    let n = file.read(&mut magic)?;
-    file.seek(SeekFrom::Start(0))?; // rewind after peek
-
-    let reader: Box<dyn Read> = if n == 2 && magic == [0x1F, 0x8B] {
-        Box::new(GzDecoder::new(file))
-    } else {
-        Box::new(file)
-    };
-
-    let reader = BufReader::new(reader);
-
-    let needle_lower = search_text.to_ascii_lowercase();
-    let search_len = needle_lower.len();
-    let mut overlap = String::new();
-
-    let total_chars = if snippet_chars == 0 { 120 } else { snippet_chars };
-    let half = total_chars / 2;
-
-    for line in reader.lines() {
-        let line = line?;
-        let combined = format!("{}{}", overlap, line);
-        let combined_lower = combined.to_ascii_lowercase();
-
-        if let Some(pos) = combined_lower.find(&needle_lower) {
-            let match_start = pos;
-            let match_end = pos + search_len;
-
-            let start = match_start.saturating_sub(half);
-            let end = std::cmp::min(combined.len(), match_end + half);
+    scan::stream_snippet(open_project_reader(path)?, query, snippet_chars)
+}
 
-            // Ensure we slice on char boundaries
-            let start = combined.char_indices().map(|(i, _)| i).take_while(|i| *i <= start).last().unwrap_or(0);
-            let end = combined.char_indices().map(|(i, _)| i).take_while(|i| *i <= end).last().unwrap_or(combined.len());
+// Cut roughly `half` chars of context on each side of the match at [match_start, match_end)
+fn make_snippet(combined: &str, match_start: usize, match_end: usize, half: usize) -> String {
+    let start = match_start.saturating_sub(half);
+    let end = std::cmp::min(combined.len(), match_end + half);
 
-            let mut snippet = combined[start..end].to_string();
-            // Compact whitespace/newlines (though `line` doesn't include newlines)
-            snippet = snippet.replace('\t', " ");
+    // Ensure we slice on char boundaries
+    let start = combined.char_indices().map(|(i, _)| i).take_while(|i| *i <= start).last().unwrap_or(0);
+    let end = combined.char_indices().map(|(i, _)| i).take_while(|i| *i <= end).last().unwrap_or(combined.len());
 
-            let prefix = if start > 0 { "..." } else { "" };
-            let suffix = if end < combined.len() { "..." } else { "" };
+    let mut snippet = combined[start..end].to_string();
+    // Compact whitespace/newlines (though `line` doesn't include newlines)
+    snippet = snippet.replace('\t', " ");
 
-            let snippet = format!("{}{}{}", prefix, snippet, suffix);
-            return Ok(Some(snippet));
-        }
+    let prefix = if start > 0 { "..." } else { "" };
+    let suffix = if end < combined.len() { "..." } else { "" };
 
-        // Keep overlap of last (search_len - 1) chars for matches across lines
-        if combined.len() >= search_len && search_len > 0 {
-            overlap = combined
-                .chars()
-                .rev()
-                .take(search_len - 1)
-                .collect::<String>()
-                .chars()
-                .rev()
-                .collect();
-        } else {
-            overlap = combined;
-        }
-    }
-
-    Ok(None)
+    format!("{}{}{}", prefix, snippet, suffix)
 }
 
-fn is_excluded_dir(entry: &DirEntry, exclude_dirs: &Option<Vec<String>>) -> bool {
-    if let Some(ref excludes) = exclude_dirs {
-        if let Some(name) = entry.file_name().to_str() {
-            return excludes.iter().any(|exc| name.eq_ignore_ascii_case(exc));
-        }
-    }
-    false
+/// Paths queued between discovery and the search workers
+const DISCOVERY_QUEUE_LEN: usize = 1024;
+
+/// Element or attribute names that carry media file paths in a project
+fn is_path_name(n: &str) -> bool {
+    matches!(n.to_ascii_lowercase().as_str(), "absolutepath" | "filepath" | "path" | "relativepath" | "relpath")
 }
 
 fn xml_unescape(s: &str) -> String {
@@ -245,6 +416,22 @@ fn normalize_asset_path(raw: &str) -> String {
     xml_unescape(&v)
 }
 
+/// File extensions that mark a path value as a media asset
+const ASSET_EXTENSIONS: [&str; 36] = [
+    "mp4","mov","mxf","mts","m2ts","avi","mkv","wmv","m4v","3gp","mpg","mpeg",
+    "wav","mp3","aac","m4a","aif","aiff","flac","ogg",
+    "png","jpg","jpeg","tif","tiff","bmp","gif","psd","ai","svg","dng","cr2","nef","arw",
+    "prfpset","mogrt"
+];
+
+/// Normalized path of a media asset, from the value of a path element or attribute
+/// (see `is_path_name`); None when it doesn't name a media file
+fn media_asset_path(raw: &str) -> Option<String> {
+    let norm = normalize_asset_path(raw);
+    let ext = Path::new(&norm).extension()?.to_str()?.to_ascii_lowercase();
+    ASSET_EXTENSIONS.contains(&ext.as_str()).then_some(norm)
+}
+
 fn extract_assets_from_prproj(path: &Path, max_size_bytes: Option<usize>) -> Result<Vec<String>, std::io::Error> {
     // Check on-disk size limit before reading
     if let Some(max_bytes) = max_size_bytes {
@@ -255,42 +442,33 @@ fn extract_assets_from_prproj(path: &Path, max_size_bytes: Option<usize>) -> Res
     }
 
     // Open and maybe gzip-decode
-    let mut file = fs::File::open(path)?;
-    let mut magic = [0u8; 2];
-    let n = file.read(&mut magic)?;
-    file.seek(SeekFrom::Start(0))?;
-
-    let reader: Box<dyn Read> = if n == 2 && magic == [0x1F, 0x8B] {
-        Box::new(GzDecoder::new(file))
-    } else {
-        Box::new(file)
-    };
+    let reader = open_project_reader(path)?;
     let mut buf_reader = BufReader::new(reader);
 
     let mut bytes = Vec::new();
     buf_reader.read_to_end(&mut bytes)?;
+    Ok(extract_assets(&bytes))
+}
 
+/// Media asset paths in a decompressed project, sorted and without duplicates
+fn extract_assets(bytes: &[u8]) -> Vec<String> {
     // Collect candidates
     let mut seen: HashSet<String> = HashSet::new();
     let mut assets: Vec<String> = Vec::new();
-
-    let asset_exts: HashSet<&'static str> = [
-        "mp4","mov","mxf","mts","m2ts","avi","mkv","wmv","m4v","3gp",
-        "wav","mp3","aac","m4a","aif","aiff","flac","ogg",
-        "png","jpg","jpeg","tif","tiff","bmp","gif","psd","ai","svg","dng","cr2","nef","arw",
-        "prfpset","mogrt"
-    ].into_iter().collect();
+    let mut add = |raw: &str| {
+        if let Some(norm) = media_asset_path(raw) {
+            if seen.insert(norm.to_lowercase()) {
+                assets.push(norm);
+            }
+        }
+    };
 
     // Initialize XML reader
-    let mut reader = Reader::from_reader(bytes.as_slice());
+    let mut reader = Reader::from_reader(bytes);
     reader.trim_text(true);
     let mut buf = Vec::new();
     let mut want_text = false;
 
-    fn is_path_name(n: &str) -> bool {
-        matches!(n.to_ascii_lowercase().as_str(), "absolutepath" | "filepath" | "path" | "relativepath" | "relpath")
-    }
-
     loop {
         match reader.read_event_into(&mut buf) {
             Ok(Event::Start(e)) => {
@@ -300,56 +478,33 @@ fn extract_assets_from_prproj(path: &Path, max_size_bytes: Option<usize>) -> Res
                 if is_path_tag {
                     want_text = true;
                 }
-                for a in e.attributes().with_checks(false) {
-                    if let Ok(attr) = a {
-                        let key = String::from_utf8_lossy(attr.key.as_ref());
-                        if is_path_name(&key) {
-                            if let Ok(val) = attr.unescape_value() {
-                                let norm = normalize_asset_path(&val);
-                                if let Some(ext) = Path::new(&norm).extension().and_then(|e| e.to_str()) {
-                                    let key = norm.to_lowercase();
-                                    if asset_exts.contains(&ext.to_ascii_lowercase()[..]) && seen.insert(key) {
-                                        assets.push(norm);
-                                    }
-                                }
-                            }
+                for attr in e.attributes().with_checks(false).flatten() {
+                    let key = String::from_utf8_lossy(attr.key.as_ref());
+                    if is_path_name(&key) {
+                        if let Ok(val) = attr.unescape_value() {
+                            add(&val);
                         }
                     }
                 }
             }
             Ok(Event::Empty(e)) => {
                 // Handle attributes on empty tags
-                for a in e.attributes().with_checks(false) {
-                    if let Ok(attr) = a {
-                        let key = String::from_utf8_lossy(attr.key.as_ref());
-                        if is_path_name(&key) {
-                            if let Ok(val) = attr.unescape_value() {
-                                let norm = normalize_asset_path(&val);
-                                if let Some(ext) = Path::new(&norm).extension().and_then(|e| e.to_str()) {
-                                    let key = norm.to_lowercase();
-                                    if asset_exts.contains(&ext.to_ascii_lowercase()[..]) && seen.insert(key) {
-                                        assets.push(norm);
-                                    }
-                                }
-                            }
+                for attr in e.attributes().with_checks(false).flatten() {
+                    let key = String::from_utf8_lossy(attr.key.as_ref());
+                    if is_path_name(&key) {
+                        if let Ok(val) = attr.unescape_value() {
+                            add(&val);
                         }
                     }
                 }
                 want_text = false;
             }
-            Ok(Event::Text(t)) => {
-                if want_text {
+            Ok(Event::Text(t))
+                if want_text => {
                     if let Ok(val) = t.unescape() {
-                        let norm = normalize_asset_path(&val);
-                        if let Some(ext) = Path::new(&norm).extension().and_then(|e| e.to_str()) {
-                            let key = norm.to_lowercase();
-                            if asset_exts.contains(&ext.to_ascii_lowercase()[..]) && seen.insert(key) {
-                                assets.push(norm);
-                            }
-                        }
+                        add(&val);
                     }
                 }
-            }
             Ok(Event::End(_)) => {
                 want_text = false;
             }
@@ -361,12 +516,23 @@ fn extract_assets_from_prproj(path: &Path, max_size_bytes: Option<usize>) -> Res
     }
 
     assets.sort();
-    Ok(assets)
+    assets
 }
 
 fn main() {
     let args = Args::parse();
 
+    if let Some(command) = args.command {
+        let code = match command {
+            Command::Remap(ref remap_args) => remap::run(remap_args),
+            Command::Relink(ref relink_args) => relink::run(relink_args),
+            Command::Collect(ref collect_args) => collect::run(collect_args),
+            Command::Diff(ref diff_args) => diff::run(diff_args),
+            Command::ExportEdl(ref edl_args) => edl::run(edl_args),
+        };
+        std::process::exit(code);
+    }
+
     // Load config from file if provided
     let config = if let Some(ref config_path) = args.config {
         match load_config(config_path) {
@@ -380,13 +546,46 @@ fn main() {
         None
     };
 
-    // Merge CLI args with config (CLI takes precedence); if none provided and not in --list-assets mode, prompt interactively
-    let mut search_text_opt = args
-        .search_text
-        .or_else(|| config.as_ref().and_then(|c| c.search_text.clone()));
+    // Listing modes treat SEARCH_TEXT as an optional filter
+    let listing = args.list_assets || args.list_sequences || args.check_media || args.uses.is_some();
+
+    // Multi-term search: terms from the CLI, else (without a CLI SEARCH_TEXT) from the config
+    let term_list: Vec<String> = if listing {
+        Vec::new()
+    } else if !args.terms.is_empty() || args.terms_file.is_some() {
+        collect_terms(&args.terms, args.terms_file.as_deref())
+    } else if args.search_text.is_none() {
+        match config.as_ref() {
+            Some(c) => collect_terms(c.search_terms.as_deref().unwrap_or_default(), c.terms_file.as_deref()),
+            None => Vec::new(),
+        }
+    } else {
+        Vec::new()
+    };
+    let term_set: Option<TermSet> = if term_list.is_empty() {
+        None
+    } else {
+        match TermSet::new(&term_list) {
+            Ok(set) => Some(set),
+            Err(e) => {
+                eprintln!("Error: invalid search terms: {}", e);
+                std::process::exit(1);
+            }
+        }
+    };
+
+    // Merge CLI args with config (CLI takes precedence); if none provided and not in a listing mode, prompt interactively
+    let mut search_text_opt = if term_set.is_some() {
+        None
+    } else {
+        args.search_text
+            .clone()
+            .or_else(|| config.as_ref().and_then(|c| c.search_text.clone()))
+    };
 
-    if !args.list_assets {
-        if search_text_opt.is_none() {
+    if !listing
+        && term_set.is_none()
+        && search_text_opt.is_none() {
             println!("No search text provided via CLI or config. Please enter the text to search for:");
             print!("> ");
             io::stdout().flush().ok();
@@ -407,15 +606,29 @@ fn main() {
                 }
             }
         }
-    }
 
-    // In list-assets mode, SEARCH_TEXT is an optional filter; in search mode, it must be present
-    let required_search_text: Option<String> = if args.list_assets {
+    // In listing modes, SEARCH_TEXT is an optional filter; in search mode, it must be present
+    let required_search_text: Option<String> = if listing || term_set.is_some() {
         None
     } else {
         Some(search_text_opt.clone().expect("search text must be set"))
     };
 
+    let use_regex = args.regex || config.as_ref().map(|c| c.regex).unwrap_or(false);
+
+    // Parse the query up front so an invalid expression or pattern fails before the walk starts.
+    // In listing modes it doubles as the (optional) asset or sequence name filter.
+    let query: Option<Query> = match search_text_opt.as_deref() {
+        Some(text) => match Query::parse(text, use_regex) {
+            Ok(q) => Some(q),
+            Err(e) => {
+                eprintln!("Error: invalid search query: {}", e);
+                std::process::exit(1);
+            }
+        },
+        None => None,
+    };
+
     let threads = args
         .threads
         .or_else(|| config.as_ref().and_then(|c| c.threads));
@@ -468,10 +681,16 @@ fn main() {
         seen.insert(key)
     });
 
+    // Watch events carry absolute paths; give the scan the same form, so the results of
+    // the first scan are recognized (and depth is measured) when their files change
+    if args.watch {
+        search_paths = search_paths.iter().map(|p| absolute_root(p)).collect();
+    }
+
     let path_source = if source_parts.len() > 1 {
         format!("{} (merged)", source_parts.join("+"))
     } else {
-        source_parts.get(0).cloned().unwrap_or("unknown").to_string()
+        source_parts.first().cloned().unwrap_or("unknown").to_string()
     };
 
     let extensions = config
@@ -492,6 +711,49 @@ fn main() {
 
     let exclude_dirs = config.as_ref().and_then(|c| c.exclude_dirs.clone());
 
+    // Include / exclude globs from config and CLI apply together, plus each root's ignore file
+    let mut include_globs = config.as_ref().and_then(|c| c.include.clone()).unwrap_or_default();
+    include_globs.extend(args.include.iter().cloned());
+    let mut exclude_globs = config.as_ref().and_then(|c| c.exclude.clone()).unwrap_or_default();
+    exclude_globs.extend(args.exclude.iter().cloned());
+    let path_rules = match PathRules::new(&include_globs, &exclude_globs, &search_paths) {
+        Ok(rules) => rules,
+        Err(e) => {
+            eprintln!("Error: invalid include/exclude pattern: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    // Date, size and depth filters: CLI flags override config keys
+    let modified_after_text = args
+        .modified_after
+        .clone()
+        .or_else(|| config.as_ref().and_then(|c| c.modified_after.clone()));
+    let modified_before_text = args
+        .modified_before
+        .clone()
+        .or_else(|| config.as_ref().and_then(|c| c.modified_before.clone()));
+    let parse_time = |text: &Option<String>| {
+        text.as_deref().map(|t| {
+            parse_time_filter(t).unwrap_or_else(|e| {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            })
+        })
+    };
+    let modified_after = parse_time(&modified_after_text);
+    let modified_before = parse_time(&modified_before_text);
+    let min_file_size_kb = args
+        .min_size_kb
+        .or_else(|| config.as_ref().and_then(|c| c.min_file_size_kb))
+        .filter(|&kb| kb > 0);
+    let max_depth = args.max_depth.or_else(|| config.as_ref().and_then(|c| c.max_depth));
+
+    let walk_threads = args
+        .walk_threads
+        .or_else(|| config.as_ref().and_then(|c| c.walk_threads))
+        .unwrap_or(0);
+
     // Set up thread pool
     if let Some(threads) = threads {
         rayon::ThreadPoolBuilder::new()
@@ -501,25 +763,174 @@ fn main() {
     }
 
 
-    let list_assets = args.list_assets;
-    if list_assets {
-        println!("Listing assets used in Premiere project files");
-        if let Some(ref f) = search_text_opt {
-            println!("Asset filter (case-insensitive): '{}'", f);
+    let scopes: Option<Vec<ScopeKind>> = args
+        .scope
+        .clone()
+        .or_else(|| config.as_ref().and_then(|c| c.scope.clone()))
+        .filter(|s| !s.is_empty());
+
+    // Listing modes first, then the search variants from most to least detailed
+    let mode = if args.list_assets {
+        Mode::ListAssets
+    } else if let Some(ref file) = args.uses {
+        Mode::Uses(file.clone(), MediaTarget::new(file))
+    } else if args.check_media {
+        Mode::CheckMedia
+    } else if args.list_sequences {
+        Mode::ListSequences
+    } else if let Some(terms) = term_set {
+        Mode::Terms(terms)
+    } else if args.all_matches {
+        Mode::AllMatches
+    } else if scopes.is_some() {
+        Mode::Scoped
+    } else if args.show_snippets {
+        Mode::Snippets
+    } else {
+        Mode::Contains
+    };
+    let show_snippets = args.show_snippets && !listing;
+
+    let format = args
+        .format
+        .or_else(|| config.as_ref().and_then(|c| c.format))
+        .unwrap_or_default();
+    let human = format == OutputFormat::Human;
+
+    let verbose_errors = args.verbose_errors || config.as_ref().map(|c| c.verbose_errors).unwrap_or(false);
+
+    let sort_key = args.sort.or_else(|| config.as_ref().and_then(|c| c.sort));
+
+    let autosave_mode = args.autosave.or_else(|| config.as_ref().and_then(|c| c.autosave));
+
+    let watching = args.watch;
+    if watching && format == OutputFormat::Json {
+        eprintln!("Error: --watch streams results as they change; use --format ndjson instead of json");
+        std::process::exit(1);
+    }
+    if watching && autosave_mode.is_some() {
+        eprintln!("Error: --autosave groups results when the scan ends and cannot be combined with --watch");
+        std::process::exit(1);
+    }
+
+    let use_index = args.index || args.index_dir.is_some() || config.as_ref().map(|c| c.index).unwrap_or(false);
+    let index: Option<Index> = if use_index {
+        let dir = args.index_dir.clone().or_else(|| config.as_ref().and_then(|c| c.index_dir.clone()));
+        match Index::open(dir) {
+            Ok(index) => Some(index),
+            Err(e) => {
+                eprintln!("Error: cannot open index: {}", e);
+                std::process::exit(1);
+            }
         }
     } else {
-        let st = required_search_text.as_ref().expect("search text must be set");
-        println!("Searching for: '{}'", st);
+        None
+    };
+
+    match mode {
+        Mode::ListAssets => {
+            status!(human, "Listing assets used in Premiere project files");
+            if let Some(ref f) = search_text_opt {
+                if use_regex {
+                    status!(human, "Asset filter (regex, case-insensitive): '{}'", f);
+                } else {
+                    status!(human, "Asset filter (case-insensitive): '{}'", f);
+                }
+            }
+        }
+        Mode::Uses(ref file, _) => {
+            status!(human, "Looking up projects that use: '{}'", file);
+        }
+        Mode::CheckMedia => {
+            status!(human, "Checking media referenced by Premiere project files");
+            if let Some(ref f) = search_text_opt {
+                if use_regex {
+                    status!(human, "Asset filter (regex, case-insensitive): '{}'", f);
+                } else {
+                    status!(human, "Asset filter (case-insensitive): '{}'", f);
+                }
+            }
+        }
+        Mode::ListSequences => {
+            status!(human, "Listing sequences in Premiere project files");
+            if let Some(ref f) = search_text_opt {
+                if use_regex {
+                    status!(human, "Sequence name filter (regex, case-insensitive): '{}'", f);
+                } else {
+                    status!(human, "Sequence name filter (case-insensitive): '{}'", f);
+                }
+            }
+        }
+        Mode::Terms(ref terms) => {
+            status!(human, "Searching for {} terms (case-insensitive)", terms.labels.len());
+        }
+        _ => {
+            let st = required_search_text.as_ref().expect("search text must be set");
+            if use_regex {
+                status!(human, "Searching for (regex): '{}'", st);
+            } else {
+                status!(human, "Searching for: '{}'", st);
+            }
+        }
     }
-    println!("Search paths ({}): {:?}", path_source, search_paths);
-    println!("Extensions: {:?}", extensions);
+    if let Some(q) = query.as_ref().filter(|q| q.is_boolean()) {
+        status!(human, "Query terms: {:?}", q.labels);
+    }
+    if let Some(ref kinds) = scopes {
+        if !listing {
+            let names: Vec<&str> = kinds.iter().map(|k| k.as_str()).collect();
+            status!(human, "Scope: {}", names.join(", "));
+        }
+    }
+    status!(human, "Search paths ({}): {:?}", path_source, search_paths);
+    status!(human, "Extensions: {:?}", extensions);
     if let Some(ref excludes) = exclude_dirs {
-        println!("Excluding directories: {:?}", excludes);
+        status!(human, "Excluding directories: {:?}", excludes);
+    }
+    if !include_globs.is_empty() {
+        status!(human, "Include: {:?}", include_globs);
+    }
+    if !exclude_globs.is_empty() {
+        status!(human, "Exclude: {:?}", exclude_globs);
+    }
+    for file in &path_rules.ignore_files {
+        status!(human, "Ignore file: {}", file.display());
     }
     if let Some(max_mb) = max_file_size_mb {
-        println!("Max file size: {} MB", max_mb);
+        status!(human, "Max file size: {} MB", max_mb);
+    }
+    if let Some(min_kb) = min_file_size_kb {
+        status!(human, "Min file size: {} KB", min_kb);
+    }
+    if let Some(ref after) = modified_after_text {
+        status!(human, "Modified after: {}", after);
+    }
+    if let Some(ref before) = modified_before_text {
+        status!(human, "Modified before: {}", before);
+    }
+    if let Some(depth) = max_depth {
+        status!(human, "Max depth: {}", depth);
+    }
+    if let Some(ref index) = index {
+        status!(human, "Index: {}", index.dir().display());
+    }
+    if let Some(mode) = autosave_mode {
+        let what = match mode {
+            AutoSaveMode::Newest => "newest matching copy",
+            AutoSaveMode::All => "all matching versions",
+        };
+        status!(human, "Auto-save copies: grouped by project ({})", what);
     }
-    println!("Scanning for files...\n");
+    if let Some(key) = sort_key {
+        let order = match key {
+            SortKey::Path => "path",
+            SortKey::Modified => "modified time, newest first",
+            SortKey::Size => "size, largest first",
+            SortKey::Matches => "match count, most first",
+        };
+        status!(human, "Sorted by: {} (results shown when the scan ends)", order);
+    }
+    status!(human, "Scanning for files...\n");
 
     // Ctrl+C (SIGINT) graceful interruption
     let interrupted = Arc::new(AtomicBool::new(false));
@@ -535,185 +946,171 @@ fn main() {
         }
     }
 
-    // Collect all matching files first
-    let mut target_files = Vec::new();
-    for path in &search_paths {
-        if interrupted.load(Ordering::SeqCst) {
-            break;
-        }
-        if !path.exists() {
-            eprintln!("Warning: Path does not exist: {:?}", path);
-            continue;
-        }
-
-        for entry in WalkDir::new(path)
-            .follow_links(follow_links)
-            .into_iter()
-            .filter_entry(|e| !is_excluded_dir(e, &exclude_dirs))
-            .filter_map(|e| e.ok())
-        {
-            if interrupted.load(Ordering::SeqCst) {
-                break;
-            }
-            if entry.file_type().is_file() {
-                if let Some(ext) = entry.path().extension() {
-                    if extensions.iter().any(|e| ext.eq_ignore_ascii_case(e)) {
-                        // Check file size if limit is set
-                        if let Some(max_bytes) = max_file_size_bytes {
-                            if let Ok(metadata) = entry.metadata() {
-                                if metadata.len() > max_bytes as u64 {
-                                    continue; // Skip files that are too large
-                                }
-                            }
-                        }
-                        target_files.push(entry.path().to_path_buf());
-                    }
-                }
-            }
-        }
-    }
-
-    let total_files = target_files.len();
-    println!("Found {} files to search\n", total_files);
-
-    if interrupted.load(Ordering::SeqCst) {
-        eprintln!("Interrupted during file discovery. Found {} files so far.", total_files);
-        println!("\n{}", "=".repeat(60));
-        println!("Search interrupted by user before processing.");
-        println!("Files discovered: {}", total_files);
-        println!("{}", "=".repeat(60));
-        std::process::exit(130);
-    }
-
-    if total_files == 0 {
-        println!("No files found.");
-        return;
-    }
+    let output = Output::new(format, show_snippets, verbose_errors);
 
-    // Set up progress bar
-    let progress = ProgressBar::new(total_files as u64);
+    // Set up progress bar: processed / discovered so far
+    let progress = ProgressBar::new(0);
     progress.set_style(
         ProgressStyle::default_bar()
-            .template("[{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} files ({per_sec})")
+            .template("[{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} files ({per_sec}) {msg}")
             .unwrap()
             .progress_chars("=>-"),
     );
+    progress.set_message("discovering...");
 
     // Counters for statistics
+    let files_found = AtomicUsize::new(0);
     let files_processed = Arc::new(AtomicUsize::new(0));
-    let files_matched = Arc::new(AtomicUsize::new(0));
-    let total_assets = Arc::new(AtomicUsize::new(0));
     let errors = Arc::new(AtomicUsize::new(0));
+    let tally = Tally::default();
+    // Auto-save copies held back until the scan ends, then reported per project
+    let autosaves = autosave_mode.map(AutoSaves::new);
+    // With --sort, results are held back and emitted in order after the scan
+    let sorted = sort_key.map(SortedResults::new);
+    let emit = |record: Record| match sorted {
+        Some(ref sorted) => sorted.hold(record),
+        None => output.emit(record),
+    };
+    // Results by path, kept for --watch to tell new and changed results from repeats
+    let known: Mutex<HashMap<PathBuf, String>> = Mutex::new(HashMap::new());
+
+    let discover_options = DiscoverOptions {
+        follow_links,
+        exclude_dirs,
+        extensions,
+        min_size_bytes: min_file_size_kb.map(|kb| kb * 1024),
+        modified_after,
+        modified_before,
+        max_depth,
+        rules: path_rules,
+        walk_threads,
+    };
 
-    let show_snippets = args.show_snippets && !list_assets;
-    let snippet_chars = args.snippet_chars;
-
-    // For search mode, capture the required search text once
-    let search_text_for_search_mode = required_search_text.clone();
-    let asset_filter = search_text_opt.clone();
+    let search = Search {
+        mode,
+        query,
+        scopes,
+        index,
+        max_size_bytes: max_file_size_bytes,
+        snippet_chars: args.snippet_chars,
+    };
 
-    // Search files in parallel with early-exit on Ctrl+C
+    // Discovery feeds a bounded queue that the workers drain while the walk is still
+    // running, so results appear right away and memory stays flat on full-drive scans
+    let (tx, rx) = mpsc::sync_channel::<PathBuf>(DISCOVERY_QUEUE_LEN);
     let interrupted_clone = Arc::clone(&interrupted);
-    let search_result: Result<(), ()> = target_files.par_iter().try_for_each(|path| {
-        if interrupted_clone.load(Ordering::SeqCst) {
-            return Err(());
-        }
+    let search_result: Result<(), ()> = thread::scope(|scope| {
+        scope.spawn(|| {
+            let tx = tx;
+            discover_files(&search_paths, &discover_options, &interrupted, |path| {
+                files_found.fetch_add(1, Ordering::Relaxed);
+                progress.inc_length(1);
+                // Fails once the workers have stopped early
+                tx.send(path).is_ok()
+            });
+            progress.set_message("");
+        });
+
+        // Search files in parallel with early-exit on Ctrl+C
+        rx.into_iter().par_bridge().try_for_each(|path| {
+            if interrupted_clone.load(Ordering::SeqCst) {
+                return Err(());
+            }
 
-        let files_processed = Arc::clone(&files_processed);
-        let files_matched = Arc::clone(&files_matched);
-        let total_assets = Arc::clone(&total_assets);
-        let errors = Arc::clone(&errors);
-
-        if list_assets {
-            match extract_assets_from_prproj(path, max_file_size_bytes) {
-                Ok(mut assets) => {
-                    // Optional filter by substring (case-insensitive)
-                    if let Some(ref filt) = asset_filter {
-                        let needle = filt.to_ascii_lowercase();
-                        assets.retain(|a| a.to_ascii_lowercase().contains(&needle));
-                    }
-                    if !assets.is_empty() {
-                        println!("\nProject: {}", path.display());
-                        for a in &assets {
-                            println!("  - {}", a);
-                        }
-                        total_assets.fetch_add(assets.len(), Ordering::Relaxed);
-                        files_matched.fetch_add(1, Ordering::Relaxed);
+            let files_processed = Arc::clone(&files_processed);
+            let errors = Arc::clone(&errors);
+
+            let result = match autosaves {
+                Some(ref autosaves) => search.run(&path).map(|r| r.and_then(|r| autosaves.hold(&path, r))),
+                None => search.run(&path),
+            };
+            match result {
+                Ok(Some(record)) => {
+                    // Print match immediately
+                    tally.add(&record);
+                    if watching {
+                        known.lock().unwrap().insert(path.clone(), record_key(&record));
                     }
-                }
-                Err(_) => {
-                    errors.fetch_add(1, Ordering::Relaxed);
-                }
-            }
-        } else if show_snippets {
-            let st = search_text_for_search_mode.as_ref().expect("search text");
-            match file_snippet_case_insensitive(path, st, max_file_size_bytes, snippet_chars) {
-                Ok(Some(snippet)) => {
-                    println!("\n✓ MATCH: {}", path.display());
-                    println!("    {}", snippet);
-                    files_matched.fetch_add(1, Ordering::Relaxed);
+                    emit(record);
                 }
                 Ok(None) => {}
-                Err(_) => {
-                    errors.fetch_add(1, Ordering::Relaxed);
-                }
-            }
-        } else {
-            let st = search_text_for_search_mode.as_ref().expect("search text");
-            match file_contains_case_insensitive(path, st, max_file_size_bytes) {
-                Ok(true) => {
-                    // Print match immediately
-                    println!("\n✓ MATCH: {}", path.display());
-                    files_matched.fetch_add(1, Ordering::Relaxed);
-                }
-                Ok(false) => {}
-                Err(_) => {
-                    // Silently skip files that can't be read (permissions, binary files, etc.)
+                Err(e) => {
+                    // Skip files that can't be read (permissions, binary files, etc.)
                     errors.fetch_add(1, Ordering::Relaxed);
+                    let record = Record::Error { path: path.display().to_string(), category: categorize(&e), error: e.to_string() };
+                    tally.add(&record);
+                    emit(record);
                 }
             }
-        }
 
-        files_processed.fetch_add(1, Ordering::Relaxed);
-        progress.inc(1);
+            files_processed.fetch_add(1, Ordering::Relaxed);
+            progress.inc(1);
 
-        if interrupted_clone.load(Ordering::SeqCst) {
-            Err(())
-        } else {
-            Ok(())
-        }
+            if interrupted_clone.load(Ordering::SeqCst) {
+                Err(())
+            } else {
+                Ok(())
+            }
+        })
     });
 
     progress.finish_and_clear();
 
-    let was_interrupted = interrupted.load(Ordering::SeqCst) || search_result.is_err();
-
-    // Print summary
-    println!("\n{}", "=".repeat(60));
-    if was_interrupted {
-        println!("Search interrupted by user (partial results):");
-    } else {
-        println!("Search complete!");
+    for record in autosaves.map(AutoSaves::finish).unwrap_or_default() {
+        tally.add(&record);
+        emit(record);
     }
-    println!(
-        "Files processed: {}",
-        files_processed.load(Ordering::Relaxed)
-    );
-    if list_assets {
-        println!("Projects with listed assets: {}", files_matched.load(Ordering::Relaxed));
-        println!("Total assets listed: {}", total_assets.load(Ordering::Relaxed));
-    } else {
-        println!("Matches found: {}", files_matched.load(Ordering::Relaxed));
+    for record in sorted.map(SortedResults::finish).unwrap_or_default() {
+        output.emit(record);
     }
 
-    let error_count = errors.load(Ordering::Relaxed);
-    if error_count > 0 {
-        println!("Files skipped (errors): {}", error_count);
+    if let Some(ref index) = search.index {
+        let (reused, refreshed) = index.stats();
+        status!(human, "\nIndex: {} up to date, {} re-read", reused, refreshed);
+    }
+
+    let was_interrupted = interrupted.load(Ordering::SeqCst) || search_result.is_err();
+
+    let total_files = files_found.load(Ordering::Relaxed);
+    if total_files == 0 && !was_interrupted {
+        if human {
+            println!("No files found.");
+        } else {
+            output.finish(&Summary::default());
+        }
+        // New projects may still appear under the watched paths
+        if !watching {
+            return;
+        }
+    } else {
+        output.finish(&Summary {
+            interrupted: was_interrupted,
+            files_found: total_files,
+            files_processed: files_processed.load(Ordering::Relaxed),
+            files_skipped: errors.load(Ordering::Relaxed),
+            autosave_copies: autosave_mode.map(|_| tally.autosave_copies()),
+            ..tally.summary(&search.mode)
+        });
     }
-    println!("{}", "=".repeat(60));
 
     if was_interrupted {
         // Use 130 as a conventional exit code for Ctrl+C
         std::process::exit(130);
     }
+
+    if watching {
+        status!(human, "\nWatching {} path(s) for changes (Ctrl+C to stop)...", search_paths.len());
+        let is_target = |p: &Path| discover_options.is_target(p, &search_paths);
+        let known = known.into_inner().unwrap();
+        if let Err(e) = watch(&search_paths, is_target, &search, &output, known, &interrupted) {
+            eprintln!("Error: cannot watch search paths: {}", e);
+            std::process::exit(1);
+        }
+        status!(human, "Stopped watching.");
+        return;
+    }
+
+    if matches!(search.mode, Mode::CheckMedia) && tally.media_missing() > 0 {
+        std::process::exit(1);
+    }
 }
@@ -0,0 +1,138 @@
+// Media availability check (--check-media): resolve each asset path of a project
+// on disk, falling back to its RelativePath against the project's directory.
+
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::project::Project;
+use crate::{extract_assets_from_prproj, normalize_asset_path};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MediaStatus {
+    /// The stored absolute path exists
+    Online,
+    /// Only the relative path, resolved against the project's directory, exists
+    FoundViaRelative,
+    Missing,
+}
+
+impl MediaStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MediaStatus::Online => "online",
+            MediaStatus::FoundViaRelative => "found_via_relative",
+            MediaStatus::Missing => "missing",
+        }
+    }
+}
+
+pub struct MediaCheck {
+    /// Asset path as listed by --list-assets
+    pub asset: String,
+    pub status: MediaStatus,
+    /// Where the file was found, when it differs from `asset`
+    pub resolved: Option<String>,
+}
+
+/// Drive-letter, UNC or rooted path (after normalize_asset_path, separators are '\')
+pub fn is_absolute_asset(asset: &str) -> bool {
+    let b = asset.as_bytes();
+    asset.starts_with('\\') || (b.len() >= 2 && b[0].is_ascii_alphabetic() && b[1] == b':')
+}
+
+/// Asset paths use '\' separators; convert them for the local filesystem
+pub fn asset_fs_path(asset: &str) -> PathBuf {
+    if cfg!(windows) {
+        PathBuf::from(asset)
+    } else {
+        PathBuf::from(asset.replace('\\', "/"))
+    }
+}
+
+pub fn file_name_of(asset: &str) -> &str {
+    asset.rsplit('\\').next().unwrap_or(asset)
+}
+
+/// Relative path against the project folder, without leading .\ components
+pub fn resolve_relative(project_dir: &Path, relative: &str) -> Option<PathBuf> {
+    let mut rel = relative;
+    while let Some(rest) = rel.strip_prefix(".\\") {
+        rel = rest;
+    }
+    let candidate = project_dir.join(asset_fs_path(rel));
+    candidate.exists().then_some(candidate)
+}
+
+/// Current RelativePath of each media file, keyed by its lowercased absolute paths
+fn relative_paths(path: &Path, max_size_bytes: Option<usize>) -> HashMap<String, String> {
+    let mut map = HashMap::new();
+    // Best effort: the asset extractor tolerates XML the model loader rejects
+    let Ok(project) = Project::load(path, max_size_bytes) else {
+        return map;
+    };
+    for media in &project.media {
+        let Some(ref relative) = media.relative_path else {
+            continue;
+        };
+        let relative = normalize_asset_path(relative);
+        for absolute in [&media.file_path, &media.actual_media_file_path].into_iter().flatten() {
+            map.insert(normalize_asset_path(absolute).to_lowercase(), relative.clone());
+        }
+    }
+    map
+}
+
+/// Classify every asset of a project. Absolute assets fall back to their media's
+/// RelativePath; relative entries sharing a file name with an absolute asset are
+/// older forms of it, and any others are checked on their own.
+pub fn check_project_media(path: &Path, max_size_bytes: Option<usize>) -> Result<Vec<MediaCheck>, io::Error> {
+    let assets = extract_assets_from_prproj(path, max_size_bytes)?;
+    let project_dir = path.parent().unwrap_or_else(|| Path::new("."));
+
+    let relative_for = relative_paths(path, max_size_bytes);
+
+    let (absolute, relative): (Vec<&str>, Vec<&str>) = assets
+        .iter()
+        .map(|a| a.as_str())
+        .partition(|a| is_absolute_asset(a));
+    let absolute_names: HashSet<String> = absolute.iter().map(|a| file_name_of(a).to_lowercase()).collect();
+    let mut checks = Vec::with_capacity(assets.len());
+
+    for asset in absolute {
+        let check = if asset_fs_path(asset).exists() {
+            MediaCheck { asset: asset.to_string(), status: MediaStatus::Online, resolved: None }
+        } else if let Some(found) = relative_for
+            .get(&asset.to_lowercase())
+            .and_then(|r| resolve_relative(project_dir, r))
+        {
+            MediaCheck {
+                asset: asset.to_string(),
+                status: MediaStatus::FoundViaRelative,
+                resolved: Some(found.display().to_string()),
+            }
+        } else {
+            MediaCheck { asset: asset.to_string(), status: MediaStatus::Missing, resolved: None }
+        };
+        checks.push(check);
+    }
+
+    for asset in relative {
+        if absolute_names.contains(&file_name_of(asset).to_lowercase()) {
+            continue;
+        }
+        let check = match resolve_relative(project_dir, asset) {
+            Some(found) => MediaCheck {
+                asset: asset.to_string(),
+                status: MediaStatus::FoundViaRelative,
+                resolved: Some(found.display().to_string()),
+            },
+            None => MediaCheck { asset: asset.to_string(), status: MediaStatus::Missing, resolved: None },
+        };
+        checks.push(check);
+    }
+
+    Ok(checks)
+}
@@ -0,0 +1,192 @@
+// Every-occurrence search (--all-matches): reports each hit with its line number
+// in the decompressed XML and the enclosing element path.
+
+use std::fs;
+use std::io::{self, BufRead, BufReader};
+use std::path::Path;
+
+use crate::query::Query;
+use crate::scope::ScopeKind;
+use crate::{make_snippet, open_project_reader};
+
+pub struct Occurrence {
+    /// 1-based line number in the decompressed project XML
+    pub line: usize,
+    /// Enclosing element path, e.g. PremiereData/ClipProjectItem/ProjectItem/Name
+    pub element_path: String,
+    /// Index into `Query::terms`
+    pub term: usize,
+    pub snippet: String,
+}
+
+/// Tracks the open-element stack while scanning raw XML text. Tags may be split
+/// across calls (a match can land inside a tag, or a start tag can wrap lines).
+#[derive(Default)]
+struct ElementStack {
+    stack: Vec<String>,
+    in_tag: bool,
+    in_quote: Option<char>,
+    pending: String,
+}
+
+fn tag_name(tag: &str) -> &str {
+    let end = tag
+        .find(|c: char| c.is_whitespace() || c == '/' || c == '>')
+        .unwrap_or(tag.len());
+    &tag[..end]
+}
+
+impl ElementStack {
+    fn scan(&mut self, text: &str) {
+        for c in text.chars() {
+            if !self.in_tag {
+                if c == '<' {
+                    self.in_tag = true;
+                    self.pending.clear();
+                }
+                continue;
+            }
+            match (self.in_quote, c) {
+                (Some(q), _) if c == q => self.in_quote = None,
+                (Some(_), _) => {}
+                (None, '"') | (None, '\'') => self.in_quote = Some(c),
+                (None, '>') => {
+                    self.in_tag = false;
+                    self.close_tag();
+                    continue;
+                }
+                _ => {}
+            }
+            self.pending.push(c);
+        }
+    }
+
+    fn close_tag(&mut self) {
+        let tag = std::mem::take(&mut self.pending);
+        if tag.starts_with('?') || tag.starts_with('!') {
+            return;
+        }
+        if let Some(rest) = tag.strip_prefix('/') {
+            let name = tag_name(rest);
+            // Pop back to the matching element to tolerate stray end tags
+            if let Some(pos) = self.stack.iter().rposition(|n| n == name) {
+                self.stack.truncate(pos);
+            }
+        } else if !tag.ends_with('/') {
+            self.stack.push(tag_name(&tag).to_string());
+        }
+    }
+
+    /// Current element path; a match inside a start tag belongs to that element
+    fn path(&self) -> String {
+        let mut path = self.stack.join("/");
+        if self.in_tag && !self.pending.starts_with('/') {
+            let name = tag_name(&self.pending);
+            if !name.is_empty() {
+                if !path.is_empty() {
+                    path.push('/');
+                }
+                path.push_str(name);
+            }
+        }
+        path
+    }
+
+    /// Name of the innermost element (including a start tag still being read)
+    fn innermost(&self) -> Option<&str> {
+        if self.in_tag && !self.pending.starts_with('/') {
+            let name = tag_name(&self.pending);
+            if !name.is_empty() {
+                return Some(name);
+            }
+        }
+        self.stack.last().map(|s| s.as_str())
+    }
+}
+
+/// Collect every occurrence of the query's positive terms. Matches are found
+/// within a single line. Returns None when the query as a whole does not match.
+/// With `scopes`, only occurrences whose innermost element is of a selected kind count.
+pub fn file_all_matches(
+    path: &Path,
+    query: &Query,
+    scopes: Option<&[ScopeKind]>,
+    max_size_bytes: Option<usize>,
+    snippet_chars: usize,
+) -> Result<Option<Vec<Occurrence>>, io::Error> {
+    // Check file size if limit is set (on-disk size)
+    if let Some(max_bytes) = max_size_bytes {
+        let metadata = fs::metadata(path)?;
+        if metadata.len() > max_bytes as u64 {
+            return Ok(None);
+        }
+    }
+
+    let mut reader = BufReader::new(open_project_reader(path)?);
+
+    let total_chars = if snippet_chars == 0 { 120 } else { snippet_chars };
+    let half = total_chars / 2;
+
+    let mut elements = ElementStack::default();
+    let mut found = vec![false; query.terms.len()];
+    let mut occurrences = Vec::new();
+
+    // Lines are split as bytes, so invalid UTF-8 is matched around rather than failing the file
+    let mut bytes = Vec::new();
+    let mut line_idx = 0;
+    loop {
+        bytes.clear();
+        if reader.read_until(b'\n', &mut bytes)? == 0 {
+            break;
+        }
+        line_idx += 1;
+        let line = String::from_utf8_lossy(&bytes);
+        let line = line.trim_end_matches(['\n', '\r']);
+
+        let mut hits: Vec<(usize, usize, usize)> = Vec::new();
+        for (i, term) in query.terms.iter().enumerate() {
+            for (start, end) in term.find_all(line) {
+                hits.push((start, end, i));
+            }
+        }
+        hits.sort();
+
+        let mut cursor = 0;
+        for (start, end, term) in hits {
+            elements.scan(&line[cursor..start]);
+            cursor = start;
+
+            if let Some(kinds) = scopes {
+                let in_scope = elements
+                    .innermost()
+                    .and_then(ScopeKind::for_element)
+                    .is_some_and(|k| kinds.contains(&k));
+                if !in_scope {
+                    continue;
+                }
+            }
+
+            found[term] = true;
+            if query.is_positive(term) {
+                occurrences.push(Occurrence {
+                    line: line_idx,
+                    element_path: elements.path(),
+                    term,
+                    snippet: make_snippet(line, start, end, half).trim().to_string(),
+                });
+            }
+        }
+        elements.scan(&line[cursor..]);
+
+        // A NOT term turning up settles the outcome early
+        if query.evaluate(&found, false) == Some(false) {
+            return Ok(None);
+        }
+    }
+
+    if query.evaluate(&found, true).unwrap_or(false) {
+        Ok(Some(occurrences))
+    } else {
+        Ok(None)
+    }
+}
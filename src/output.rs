@@ -0,0 +1,737 @@
+// Result output: the default human-readable report plus machine-readable
+// JSON (one document), NDJSON (one record per line) and CSV formats.
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use crate::errors::ErrorCategory;
+use crate::media::MediaStatus;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    /// Human-readable report (default)
+    #[default]
+    Human,
+    /// A single JSON document written when the search finishes
+    Json,
+    /// One JSON object per line, streamed as results arrive
+    Ndjson,
+    /// Comma-separated rows: record,path,line,element,term,text
+    Csv,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ScopeRecord {
+    pub kind: String,
+    pub text: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OccurrenceRecord {
+    pub line: usize,
+    pub element_path: String,
+    /// Matched term, only set for boolean queries
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub term: Option<String>,
+    pub snippet: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SequenceRecord {
+    pub name: String,
+    /// Frames per second
+    pub frame_rate: Option<f64>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub video_tracks: usize,
+    pub audio_tracks: usize,
+    pub duration_ticks: i64,
+    /// HH:MM:SS:FF (non-drop-frame)
+    pub duration: Option<String>,
+}
+
+impl SequenceRecord {
+    fn describe(&self) -> String {
+        let rate = match self.frame_rate {
+            Some(fps) => format!("{} fps", format_fps(fps)),
+            None => "? fps".to_string(),
+        };
+        let size = match (self.width, self.height) {
+            (Some(w), Some(h)) => format!("{}x{}", w, h),
+            _ => "?x?".to_string(),
+        };
+        format!(
+            "{}, {}, {} video / {} audio tracks, duration {}",
+            rate,
+            size,
+            self.video_tracks,
+            self.audio_tracks,
+            self.duration.as_deref().unwrap_or("?")
+        )
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct MediaRecord {
+    pub asset: String,
+    pub status: MediaStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resolved: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, Copy, Serialize)]
+pub struct MediaCounts {
+    pub online: usize,
+    pub found_via_relative: usize,
+    pub missing: usize,
+}
+
+impl MediaCounts {
+    pub fn add(&mut self, status: MediaStatus) {
+        match status {
+            MediaStatus::Online => self.online += 1,
+            MediaStatus::FoundViaRelative => self.found_via_relative += 1,
+            MediaStatus::Missing => self.missing += 1,
+        }
+    }
+
+    pub fn merge(&mut self, other: &MediaCounts) {
+        self.online += other.online;
+        self.found_via_relative += other.found_via_relative;
+        self.missing += other.missing;
+    }
+
+    fn describe(&self) -> String {
+        format!(
+            "{} online, {} found via relative path, {} missing",
+            self.online, self.found_via_relative, self.missing
+        )
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct AutoSaveVersion {
+    /// YYYY-MM-DD HH:MM:SS, from the file name
+    pub saved: String,
+    pub path: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TermHits {
+    pub term: String,
+    /// Projects containing the term
+    pub projects: usize,
+}
+
+// 25 -> "25", 23.976... -> "23.976", 29.97002... -> "29.97"
+fn format_fps(fps: f64) -> String {
+    let text = format!("{:.3}", fps);
+    text.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Record {
+    Match {
+        path: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        snippet: Option<String>,
+        /// Set in --scope mode: the element kinds that matched
+        #[serde(skip_serializing_if = "Option::is_none")]
+        scopes: Option<Vec<ScopeRecord>>,
+        /// Set in --all-matches mode
+        #[serde(skip_serializing_if = "Option::is_none")]
+        occurrences: Option<Vec<OccurrenceRecord>>,
+    },
+    Assets {
+        path: String,
+        assets: Vec<String>,
+    },
+    Sequences {
+        path: String,
+        sequences: Vec<SequenceRecord>,
+    },
+    Media {
+        path: String,
+        counts: MediaCounts,
+        media: Vec<MediaRecord>,
+    },
+    Uses {
+        path: String,
+        assets: Vec<String>,
+        sequences: Vec<String>,
+        bins: Vec<String>,
+    },
+    /// Multi-term search: the terms found in a project, in term order
+    Terms {
+        path: String,
+        terms: Vec<String>,
+    },
+    /// --autosave: the auto-save copies of one project that matched, collapsed
+    AutoSave {
+        /// Project name from the auto-save file names
+        project: String,
+        /// The project next to the auto-save folder, if it exists
+        #[serde(skip_serializing_if = "Option::is_none")]
+        original: Option<String>,
+        /// Save time of the newest matching copy
+        saved: String,
+        /// Number of matching copies
+        matched: usize,
+        /// Set with --autosave all: every matching copy, newest first
+        #[serde(skip_serializing_if = "Option::is_none")]
+        versions: Option<Vec<AutoSaveVersion>>,
+        /// Result for the newest matching copy
+        record: Box<Record>,
+    },
+    /// --watch: a previously reported project no longer matches or was deleted
+    Unmatched {
+        path: String,
+        removed: bool,
+    },
+    Error {
+        path: String,
+        category: ErrorCategory,
+        error: String,
+    },
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct Summary {
+    pub interrupted: bool,
+    pub files_found: usize,
+    pub files_processed: usize,
+    pub files_matched: usize,
+    pub files_skipped: usize,
+    /// Set in --list-assets mode
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_assets: Option<usize>,
+    /// Set in --uses mode: the media file looked up
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uses: Option<String>,
+    /// Set in --list-sequences and --uses modes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_sequences: Option<usize>,
+    /// Set in --check-media mode
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub media: Option<MediaCounts>,
+    /// Set in multi-term mode: projects matching each term, in term order
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub term_hits: Option<Vec<TermHits>>,
+    /// Set in --all-matches mode
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_occurrences: Option<usize>,
+    /// Set with --autosave: matching auto-save copies, before grouping
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub autosave_copies: Option<usize>,
+    /// Skipped files per error category, when there were any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub errors_by_category: Option<BTreeMap<ErrorCategory, usize>>,
+}
+
+#[derive(Serialize)]
+struct JsonDocument<'a> {
+    results: Vec<&'a Record>,
+    errors: Vec<&'a Record>,
+    summary: &'a Summary,
+}
+
+#[derive(Serialize)]
+struct SummaryLine<'a> {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    #[serde(flatten)]
+    summary: &'a Summary,
+}
+
+const CSV_HEADER: [&str; 6] = ["record", "path", "line", "element", "term", "text"];
+
+pub struct Output {
+    format: OutputFormat,
+    show_snippets: bool,
+    /// Human output: list each failed file in a section before the summary
+    verbose_errors: bool,
+    /// Records held back until the end (JSON, and errors for --verbose-errors)
+    collected: Mutex<Vec<Record>>,
+    /// Set once the summary is written; later errors (--watch) print right away
+    finished: AtomicBool,
+    csv: Mutex<csv::Writer<io::Stdout>>,
+}
+
+impl Output {
+    pub fn new(format: OutputFormat, show_snippets: bool, verbose_errors: bool) -> Output {
+        let mut csv = csv::Writer::from_writer(io::stdout());
+        if format == OutputFormat::Csv {
+            csv.write_record(CSV_HEADER).ok();
+        }
+        Output {
+            format,
+            show_snippets,
+            verbose_errors,
+            collected: Mutex::new(Vec::new()),
+            finished: AtomicBool::new(false),
+            csv: Mutex::new(csv),
+        }
+    }
+
+    /// Emit one result. Safe to call from parallel workers.
+    pub fn emit(&self, record: Record) {
+        match self.format {
+            OutputFormat::Human
+                if self.verbose_errors
+                    && matches!(record, Record::Error { .. })
+                    && !self.finished.load(Ordering::SeqCst) =>
+            {
+                self.collected.lock().unwrap().push(record)
+            }
+            OutputFormat::Human => self.print_human(&record),
+            OutputFormat::Json => self.collected.lock().unwrap().push(record),
+            OutputFormat::Ndjson => {
+                if let Ok(line) = serde_json::to_string(&record) {
+                    println!("{}", line);
+                }
+            }
+            OutputFormat::Csv => {
+                let mut csv = self.csv.lock().unwrap();
+                for row in csv_rows(&record) {
+                    csv.write_record(&row).ok();
+                }
+                csv.flush().ok();
+            }
+        }
+    }
+
+    /// Write the final statistics (and, for JSON, the whole document)
+    pub fn finish(&self, summary: &Summary) {
+        match self.format {
+            OutputFormat::Human => {
+                if self.verbose_errors {
+                    print_error_section(&std::mem::take(&mut *self.collected.lock().unwrap()));
+                }
+                self.finished.store(true, Ordering::SeqCst);
+                print_human_summary(summary);
+            }
+            OutputFormat::Json => {
+                let collected = self.collected.lock().unwrap();
+                let (errors, results): (Vec<&Record>, Vec<&Record>) = collected
+                    .iter()
+                    .partition(|r| matches!(r, Record::Error { .. }));
+                let doc = JsonDocument { results, errors, summary };
+                match serde_json::to_string_pretty(&doc) {
+                    Ok(text) => println!("{}", text),
+                    Err(e) => eprintln!("Error writing JSON output: {}", e),
+                }
+            }
+            OutputFormat::Ndjson => {
+                let line = SummaryLine { kind: "summary", summary };
+                if let Ok(text) = serde_json::to_string(&line) {
+                    println!("{}", text);
+                }
+            }
+            OutputFormat::Csv => {
+                let mut csv = self.csv.lock().unwrap();
+                let mut stats = vec![
+                    ("interrupted", summary.interrupted.to_string()),
+                    ("files_found", summary.files_found.to_string()),
+                    ("files_processed", summary.files_processed.to_string()),
+                    ("files_matched", summary.files_matched.to_string()),
+                    ("files_skipped", summary.files_skipped.to_string()),
+                ];
+                if let Some(n) = summary.total_assets {
+                    stats.push(("total_assets", n.to_string()));
+                }
+                if let Some(ref target) = summary.uses {
+                    stats.push(("uses", target.clone()));
+                }
+                if let Some(n) = summary.total_sequences {
+                    stats.push(("total_sequences", n.to_string()));
+                }
+                if let Some(counts) = summary.media {
+                    stats.push(("media_online", counts.online.to_string()));
+                    stats.push(("media_found_via_relative", counts.found_via_relative.to_string()));
+                    stats.push(("media_missing", counts.missing.to_string()));
+                }
+                if let Some(n) = summary.total_occurrences {
+                    stats.push(("total_occurrences", n.to_string()));
+                }
+                if let Some(n) = summary.autosave_copies {
+                    stats.push(("autosave_copies", n.to_string()));
+                }
+                for (name, value) in stats {
+                    csv.write_record(["summary", "", "", name, "", &value]).ok();
+                }
+                for hits in summary.term_hits.iter().flatten() {
+                    csv.write_record(["summary", "", "", "term_projects", &hits.term, &hits.projects.to_string()])
+                        .ok();
+                }
+                for (category, count) in summary.errors_by_category.iter().flatten() {
+                    csv.write_record(["summary", "", "", "errors", category.as_str(), &count.to_string()]).ok();
+                }
+                csv.flush().ok();
+            }
+        }
+    }
+
+    fn print_human(&self, record: &Record) {
+        match record {
+            Record::Match { path, snippet, scopes, occurrences } => {
+                if let Some(occurrences) = occurrences {
+                    // Build the whole block first so parallel workers don't interleave lines
+                    let mut block = format!(
+                        "\n✓ MATCH: {} ({} {})",
+                        path,
+                        occurrences.len(),
+                        if occurrences.len() == 1 { "match" } else { "matches" }
+                    );
+                    for (n, occ) in occurrences.iter().enumerate() {
+                        block.push_str(&format!("\n    #{} line {}  {}", n + 1, occ.line, occ.element_path));
+                        if let Some(ref term) = occ.term {
+                            block.push_str(&format!("  [{}]", term));
+                        }
+                        if self.show_snippets {
+                            block.push_str(&format!("\n        {}", occ.snippet));
+                        }
+                    }
+                    println!("{}", block);
+                } else if let Some(scopes) = scopes {
+                    let kinds: Vec<&str> = scopes.iter().map(|s| s.kind.as_str()).collect();
+                    let mut block = format!("\n✓ MATCH: {} [{}]", path, kinds.join(", "));
+                    if self.show_snippets {
+                        for hit in scopes {
+                            block.push_str(&format!("\n    {}: {}", hit.kind, hit.text));
+                        }
+                    }
+                    println!("{}", block);
+                } else {
+                    match snippet {
+                        Some(s) if !s.is_empty() => println!("\n✓ MATCH: {}\n    {}", path, s),
+                        _ => println!("\n✓ MATCH: {}", path),
+                    }
+                }
+            }
+            Record::Assets { path, assets } => {
+                let mut block = format!("\nProject: {}", path);
+                for a in assets {
+                    block.push_str(&format!("\n  - {}", a));
+                }
+                println!("{}", block);
+            }
+            Record::Sequences { path, sequences } => {
+                let mut block = format!("\nProject: {}", path);
+                for seq in sequences {
+                    block.push_str(&format!("\n  - {}: {}", seq.name, seq.describe()));
+                }
+                println!("{}", block);
+            }
+            Record::Media { path, counts, media } => {
+                let mut block = format!("\nProject: {} ({})", path, counts.describe());
+                for m in media {
+                    let mark = match m.status {
+                        MediaStatus::Online => "✓",
+                        MediaStatus::FoundViaRelative => "~",
+                        MediaStatus::Missing => "✗",
+                    };
+                    block.push_str(&format!("\n  {} {}", mark, m.asset));
+                    if let Some(ref resolved) = m.resolved {
+                        block.push_str(&format!("\n      -> {}", resolved));
+                    }
+                }
+                println!("{}", block);
+            }
+            Record::Uses { path, assets, sequences, bins } => {
+                let mut block = format!("\n✓ USED BY: {}", path);
+                for a in assets {
+                    block.push_str(&format!("\n    asset: {}", a));
+                }
+                for seq in sequences {
+                    block.push_str(&format!("\n    sequence: {}", seq));
+                }
+                for bin in bins {
+                    block.push_str(&format!("\n    bin: {}", bin));
+                }
+                println!("{}", block);
+            }
+            Record::Terms { path, terms } => {
+                let mut block = format!(
+                    "\n✓ MATCH: {} ({} {})",
+                    path,
+                    terms.len(),
+                    if terms.len() == 1 { "term" } else { "terms" }
+                );
+                for term in terms {
+                    block.push_str(&format!("\n    term: {}", term));
+                }
+                println!("{}", block);
+            }
+            Record::AutoSave { project, original, saved, matched, versions, record } => {
+                let mut block = format!(
+                    "\n⟳ AUTO-SAVE: {} ({} matching {}, newest {})",
+                    project,
+                    matched,
+                    if *matched == 1 { "copy" } else { "copies" },
+                    saved
+                );
+                if let Some(original) = original {
+                    block.push_str(&format!("\n    project: {}", original));
+                }
+                for v in versions.iter().flatten() {
+                    block.push_str(&format!("\n    {}  {}", v.saved, v.path));
+                }
+                println!("{}", block);
+                self.print_human(record);
+            }
+            Record::Unmatched { path, removed } => {
+                if *removed {
+                    println!("\n✗ REMOVED: {}", path);
+                } else {
+                    println!("\n✗ NO LONGER MATCHES: {}", path);
+                }
+            }
+            // Only counted in the summary, unless listed with --verbose-errors
+            Record::Error { path, category, error } => {
+                if self.verbose_errors {
+                    println!("\n✗ ERROR ({}): {}: {}", category.describe(), path, error);
+                }
+            }
+        }
+    }
+}
+
+fn csv_rows(record: &Record) -> Vec<[String; 6]> {
+    match record {
+        Record::Match { path, snippet, scopes, occurrences } => {
+            if let Some(occurrences) = occurrences {
+                occurrences
+                    .iter()
+                    .map(|o| {
+                        [
+                            "occurrence".to_string(),
+                            path.clone(),
+                            o.line.to_string(),
+                            o.element_path.clone(),
+                            o.term.clone().unwrap_or_default(),
+                            o.snippet.clone(),
+                        ]
+                    })
+                    .collect()
+            } else if let Some(scopes) = scopes {
+                scopes
+                    .iter()
+                    .map(|s| {
+                        [
+                            "match".to_string(),
+                            path.clone(),
+                            String::new(),
+                            s.kind.clone(),
+                            String::new(),
+                            s.text.clone(),
+                        ]
+                    })
+                    .collect()
+            } else {
+                vec![[
+                    "match".to_string(),
+                    path.clone(),
+                    String::new(),
+                    String::new(),
+                    String::new(),
+                    snippet.clone().unwrap_or_default(),
+                ]]
+            }
+        }
+        Record::Assets { path, assets } => assets
+            .iter()
+            .map(|a| {
+                [
+                    "asset".to_string(),
+                    path.clone(),
+                    String::new(),
+                    String::new(),
+                    String::new(),
+                    a.clone(),
+                ]
+            })
+            .collect(),
+        Record::Sequences { path, sequences } => sequences
+            .iter()
+            .map(|seq| {
+                [
+                    "sequence".to_string(),
+                    path.clone(),
+                    String::new(),
+                    String::new(),
+                    String::new(),
+                    format!("{}: {}", seq.name, seq.describe()),
+                ]
+            })
+            .collect(),
+        Record::Media { path, media, .. } => media
+            .iter()
+            .map(|m| {
+                [
+                    "media".to_string(),
+                    path.clone(),
+                    String::new(),
+                    String::new(),
+                    String::new(),
+                    match m.resolved {
+                        Some(ref resolved) => format!("{}: {} -> {}", m.status.as_str(), m.asset, resolved),
+                        None => format!("{}: {}", m.status.as_str(), m.asset),
+                    },
+                ]
+            })
+            .collect(),
+        Record::Uses { path, assets, sequences, bins } => {
+            let assets = assets.iter().map(|a| ("uses_asset", a));
+            let sequences = sequences.iter().map(|s| ("uses_sequence", s));
+            let bins = bins.iter().map(|b| ("uses_bin", b));
+            assets
+                .chain(sequences)
+                .chain(bins)
+                .map(|(kind, text)| {
+                    [
+                        kind.to_string(),
+                        path.clone(),
+                        String::new(),
+                        String::new(),
+                        String::new(),
+                        text.clone(),
+                    ]
+                })
+                .collect()
+        }
+        Record::Terms { path, terms } => terms
+            .iter()
+            .map(|term| {
+                [
+                    "term".to_string(),
+                    path.clone(),
+                    String::new(),
+                    String::new(),
+                    term.clone(),
+                    String::new(),
+                ]
+            })
+            .collect(),
+        Record::AutoSave { project, saved, versions, record, .. } => {
+            // The newest copy's own rows, then one row per listed version
+            let mut rows = csv_rows(record);
+            let newest = [AutoSaveVersion { saved: saved.clone(), path: record_path(record).to_string() }];
+            let listed = versions.as_deref().unwrap_or(&newest);
+            rows.extend(listed.iter().map(|v| {
+                [
+                    "autosave".to_string(),
+                    v.path.clone(),
+                    String::new(),
+                    String::new(),
+                    String::new(),
+                    format!("{}: {}", project, v.saved),
+                ]
+            }));
+            rows
+        }
+        Record::Unmatched { path, removed } => vec![[
+            if *removed { "removed" } else { "unmatched" }.to_string(),
+            path.clone(),
+            String::new(),
+            String::new(),
+            String::new(),
+            String::new(),
+        ]],
+        Record::Error { path, category, error } => vec![[
+            "error".to_string(),
+            path.clone(),
+            String::new(),
+            category.as_str().to_string(),
+            String::new(),
+            error.clone(),
+        ]],
+    }
+}
+
+/// Project file a record is about (for an auto-save group, its newest matching copy)
+pub fn record_path(record: &Record) -> &str {
+    match record {
+        Record::Match { path, .. }
+        | Record::Assets { path, .. }
+        | Record::Sequences { path, .. }
+        | Record::Media { path, .. }
+        | Record::Uses { path, .. }
+        | Record::Terms { path, .. }
+        | Record::Unmatched { path, .. }
+        | Record::Error { path, .. } => path,
+        Record::AutoSave { record, .. } => record_path(record),
+    }
+}
+
+/// --verbose-errors: failed files grouped by category, each group sorted by path
+fn print_error_section(errors: &[Record]) {
+    let mut groups: BTreeMap<ErrorCategory, Vec<(&str, &str)>> = BTreeMap::new();
+    for record in errors {
+        if let Record::Error { path, category, error } = record {
+            groups.entry(*category).or_default().push((path, error));
+        }
+    }
+    if groups.is_empty() {
+        return;
+    }
+    println!("\nErrors ({}):", errors.len());
+    for (category, mut files) in groups {
+        files.sort();
+        println!("  {} ({})", category.describe(), files.len());
+        for (path, error) in files {
+            println!("    {}: {}", path, error);
+        }
+    }
+}
+
+fn print_human_summary(summary: &Summary) {
+    println!("\n{}", "=".repeat(60));
+    if summary.interrupted {
+        println!("Search interrupted by user (partial results):");
+    } else {
+        println!("Search complete!");
+    }
+    println!("Files processed: {}", summary.files_processed);
+    if let Some(total_assets) = summary.total_assets {
+        println!("Projects with listed assets: {}", summary.files_matched);
+        println!("Total assets listed: {}", total_assets);
+    } else if let Some(ref target) = summary.uses {
+        println!("Projects using '{}': {}", target, summary.files_matched);
+        println!("Sequences using it: {}", summary.total_sequences.unwrap_or(0));
+    } else if let Some(total_sequences) = summary.total_sequences {
+        println!("Projects with sequences: {}", summary.files_matched);
+        println!("Total sequences listed: {}", total_sequences);
+    } else if let Some(counts) = summary.media {
+        println!("Projects checked: {}", summary.files_matched);
+        println!("Media online: {}", counts.online);
+        println!("Media found via relative path: {}", counts.found_via_relative);
+        println!("Media missing: {}", counts.missing);
+    } else if let Some(ref hits) = summary.term_hits {
+        let found = hits.iter().filter(|h| h.projects > 0).count();
+        println!("Projects matching any term: {}", summary.files_matched);
+        println!("Terms found: {} of {}", found, hits.len());
+        for h in hits {
+            println!("  {:>6}  {}", h.projects, h.term);
+        }
+    } else {
+        println!("Matches found: {}", summary.files_matched);
+        if let Some(total) = summary.total_occurrences {
+            println!("Total occurrences: {}", total);
+        }
+    }
+
+    if let Some(copies) = summary.autosave_copies {
+        println!("Auto-save copies matched (grouped by project): {}", copies);
+    }
+
+    if summary.files_skipped > 0 {
+        println!("Files skipped (errors): {}", summary.files_skipped);
+    }
+    println!("{}", "=".repeat(60));
+}
@@ -0,0 +1,187 @@
+// Media duration probe (relink --match-duration): read how long a media file is
+// from its container header, without decoding. QuickTime/MP4 files are read from
+// the movie header (`mvhd`) and WAV files from their format and data chunks;
+// other formats are not probed.
+
+use std::fs::File;
+use std::io::{self, BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use crate::project::TICKS_PER_SECOND;
+
+/// Length of a media file in ticks, when its format is one we can read
+pub fn media_duration(path: &Path) -> Option<i64> {
+    let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+    let mut file = BufReader::new(File::open(path).ok()?);
+    match ext.as_str() {
+        "mov" | "mp4" | "m4v" | "m4a" | "3gp" => quicktime_duration(&mut file).ok().flatten(),
+        "wav" => wav_duration(&mut file).ok().flatten(),
+        _ => None,
+    }
+}
+
+fn ticks(units: u64, per_second: u64) -> Option<i64> {
+    if per_second == 0 {
+        return None;
+    }
+    i64::try_from(units as i128 * TICKS_PER_SECOND as i128 / per_second as i128).ok()
+}
+
+fn read_array<const N: usize>(input: &mut impl Read) -> io::Result<[u8; N]> {
+    let mut buf = [0; N];
+    input.read_exact(&mut buf)?;
+    Ok(buf)
+}
+
+/// Next box header: (type, payload size, or None when the box runs to the end)
+fn next_box(input: &mut impl Read) -> io::Result<([u8; 4], Option<u64>)> {
+    let size = u32::from_be_bytes(read_array(input)?) as u64;
+    let kind = read_array(input)?;
+    let payload = match size {
+        0 => None,
+        1 => Some(u64::from_be_bytes(read_array(input)?).saturating_sub(16)),
+        n => Some(n.saturating_sub(8)),
+    };
+    Ok((kind, payload))
+}
+
+/// Duration from the `mvhd` box inside the top-level `moov` box
+fn quicktime_duration(input: &mut (impl Read + Seek)) -> io::Result<Option<i64>> {
+    // Top level: skip to `moov`, which may come after the media data
+    let moov_end = loop {
+        let (kind, size) = next_box(input)?;
+        match (&kind, size) {
+            (b"moov", Some(size)) => break input.stream_position()? + size,
+            (b"moov", None) => break u64::MAX,
+            (_, Some(size)) => {
+                input.seek(SeekFrom::Current(size as i64))?;
+            }
+            (_, None) => return Ok(None),
+        }
+    };
+    while input.stream_position()? < moov_end {
+        let (kind, size) = next_box(input)?;
+        if &kind != b"mvhd" {
+            let Some(size) = size else {
+                return Ok(None);
+            };
+            input.seek(SeekFrom::Current(size as i64))?;
+            continue;
+        }
+        let [version, ..] = read_array::<4>(input)?;
+        let (timescale, duration) = if version == 1 {
+            input.seek(SeekFrom::Current(16))?;
+            let timescale = u32::from_be_bytes(read_array(input)?);
+            (timescale, u64::from_be_bytes(read_array(input)?))
+        } else {
+            input.seek(SeekFrom::Current(8))?;
+            let timescale = u32::from_be_bytes(read_array(input)?);
+            (timescale, u32::from_be_bytes(read_array(input)?) as u64)
+        };
+        return Ok(ticks(duration, timescale as u64));
+    }
+    Ok(None)
+}
+
+/// Duration from the byte rate in `fmt ` and the size of `data`
+fn wav_duration(input: &mut (impl Read + Seek)) -> io::Result<Option<i64>> {
+    let header: [u8; 12] = read_array(input)?;
+    if &header[..4] != b"RIFF" || &header[8..] != b"WAVE" {
+        return Ok(None);
+    }
+    let mut byte_rate = None;
+    loop {
+        let kind: [u8; 4] = read_array(input)?;
+        let size = u32::from_le_bytes(read_array(input)?) as u64;
+        match &kind {
+            b"fmt " => {
+                let fmt: [u8; 12] = read_array(input)?;
+                byte_rate = Some(u32::from_le_bytes([fmt[8], fmt[9], fmt[10], fmt[11]]) as u64);
+                input.seek(SeekFrom::Current(size as i64 - 12 + (size % 2) as i64))?;
+            }
+            b"data" => return Ok(byte_rate.and_then(|rate| ticks(size, rate))),
+            _ => {
+                input.seek(SeekFrom::Current((size + size % 2) as i64))?;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn mp4_box(kind: &[u8; 4], payload: &[u8]) -> Vec<u8> {
+        let mut out = ((payload.len() + 8) as u32).to_be_bytes().to_vec();
+        out.extend_from_slice(kind);
+        out.extend_from_slice(payload);
+        out
+    }
+
+    fn mvhd_v0(timescale: u32, duration: u32) -> Vec<u8> {
+        let mut payload = vec![0; 12];
+        payload.extend_from_slice(&timescale.to_be_bytes());
+        payload.extend_from_slice(&duration.to_be_bytes());
+        payload.extend_from_slice(&[0; 80]);
+        mp4_box(b"mvhd", &payload)
+    }
+
+    #[test]
+    fn quicktime_with_movie_header_after_the_media_data() {
+        let mut file = mp4_box(b"ftyp", b"qt  \0\0\0\0");
+        file.extend(mp4_box(b"mdat", &[0xAB; 1000]));
+        let mut moov = mp4_box(b"udta", &[0; 16]);
+        moov.extend(mvhd_v0(600, 6000));
+        file.extend(mp4_box(b"moov", &moov));
+        assert_eq!(quicktime_duration(&mut Cursor::new(file)).unwrap(), Some(10 * TICKS_PER_SECOND));
+    }
+
+    #[test]
+    fn quicktime_version_1_header() {
+        let mut payload = vec![1, 0, 0, 0];
+        payload.extend_from_slice(&[0; 16]);
+        payload.extend_from_slice(&1000u32.to_be_bytes());
+        payload.extend_from_slice(&2500u64.to_be_bytes());
+        let file = mp4_box(b"moov", &mp4_box(b"mvhd", &payload));
+        assert_eq!(quicktime_duration(&mut Cursor::new(file)).unwrap(), Some(5 * TICKS_PER_SECOND / 2));
+    }
+
+    #[test]
+    fn quicktime_without_movie_header() {
+        let file = mp4_box(b"ftyp", b"isom\0\0\0\0");
+        assert!(quicktime_duration(&mut Cursor::new(file)).is_err());
+        let file = mp4_box(b"moov", &mp4_box(b"trak", &[0; 8]));
+        assert_eq!(quicktime_duration(&mut Cursor::new(file)).unwrap(), None);
+    }
+
+    fn wav(byte_rate: u32, data_len: u32) -> Vec<u8> {
+        let mut fmt = vec![1, 0, 2, 0];
+        fmt.extend_from_slice(&(byte_rate / 4).to_le_bytes());
+        fmt.extend_from_slice(&byte_rate.to_le_bytes());
+        fmt.extend_from_slice(&[4, 0, 16, 0]);
+        let mut file = b"RIFF\0\0\0\0WAVE".to_vec();
+        file.extend_from_slice(b"fmt ");
+        file.extend_from_slice(&(fmt.len() as u32).to_le_bytes());
+        file.extend(fmt);
+        // An odd-sized chunk is padded to an even length
+        file.extend_from_slice(b"LIST\x03\0\0\0abc\0");
+        file.extend_from_slice(b"data");
+        file.extend_from_slice(&data_len.to_le_bytes());
+        file
+    }
+
+    #[test]
+    fn wav_length_from_data_size() {
+        let file = wav(192_000, 96_000);
+        assert_eq!(wav_duration(&mut Cursor::new(file)).unwrap(), Some(TICKS_PER_SECOND / 2));
+        let not_wav = b"RIFF\0\0\0\0AVI LIST".to_vec();
+        assert_eq!(wav_duration(&mut Cursor::new(not_wav)).unwrap(), None);
+    }
+
+    #[test]
+    fn unsupported_formats_are_not_probed() {
+        assert_eq!(media_duration(Path::new("missing.mxf")), None);
+        assert_eq!(media_duration(Path::new("missing.mov")), None);
+    }
+}
@@ -0,0 +1,777 @@
+// Typed model of a Premiere Pro project, built from the .prproj XML.
+//
+// A project is a flat list of top-level objects that point at each other with
+// ObjectRef (numeric ObjectID) and ObjectURef (ObjectUID GUID) attributes.
+// The loader parses the XML into a small DOM, indexes those objects and then
+// resolves the graph into bins, master clips, media, sequences, tracks and
+// track items. Cross references are indices into the `Project` vectors.
+
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::{self, BufReader};
+use std::path::{Path, PathBuf};
+
+use crate::errors::FileTooLarge;
+use crate::open_project_reader;
+
+/// Premiere expresses all times in ticks
+pub const TICKS_PER_SECOND: i64 = 254_016_000_000;
+
+/// Frames per second for a rate stored as ticks per frame
+pub fn frames_per_second(ticks_per_frame: i64) -> f64 {
+    TICKS_PER_SECOND as f64 / ticks_per_frame as f64
+}
+
+/// Non-drop-frame HH:MM:SS:FF timecode. Fractional rates count frames at the
+/// nominal (rounded) rate, as Premiere does for non-drop-frame display.
+pub fn ticks_to_timecode(ticks: i64, ticks_per_frame: i64) -> String {
+    if ticks_per_frame <= 0 {
+        return "00:00:00:00".to_string();
+    }
+    let frames = ticks.max(0) / ticks_per_frame;
+    let fps = (frames_per_second(ticks_per_frame).round() as i64).max(1);
+    let (ff, secs) = (frames % fps, frames / fps);
+    format!("{:02}:{:02}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60, ff)
+}
+
+#[derive(Debug, Default)]
+pub struct Project {
+    pub path: PathBuf,
+    pub root: Bin,
+    pub master_clips: Vec<MasterClip>,
+    pub media: Vec<Media>,
+    pub sequences: Vec<Sequence>,
+}
+
+#[derive(Debug, Default)]
+pub struct Bin {
+    pub name: String,
+    pub bins: Vec<Bin>,
+    pub items: Vec<ProjectItem>,
+}
+
+#[derive(Debug)]
+pub struct ProjectItem {
+    pub name: String,
+    pub master_clip: Option<usize>,
+}
+
+#[derive(Debug)]
+pub struct MasterClip {
+    pub uid: String,
+    pub name: String,
+    pub clips: Vec<Clip>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClipSource {
+    Media(usize),
+    Sequence(usize),
+}
+
+#[derive(Debug)]
+pub struct Clip {
+    pub in_point: Option<i64>,
+    pub out_point: Option<i64>,
+    pub source: Option<ClipSource>,
+    pub markers: Vec<Marker>,
+}
+
+#[derive(Debug, Default)]
+pub struct Media {
+    pub uid: String,
+    pub title: String,
+    pub file_path: Option<String>,
+    pub actual_media_file_path: Option<String>,
+    pub relative_path: Option<String>,
+    /// Timecode of the first frame in ticks: the file's own, or the one set with
+    /// Modify > Timecode
+    pub start: Option<i64>,
+    pub video: Option<StreamInfo>,
+    pub audio: Option<StreamInfo>,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct StreamInfo {
+    /// Ticks per frame (video) or per sample (audio)
+    pub frame_rate: Option<i64>,
+    /// Length in ticks
+    pub duration: Option<i64>,
+}
+
+#[derive(Debug, Default)]
+pub struct Sequence {
+    pub uid: String,
+    pub name: String,
+    /// Ticks per video frame
+    pub frame_rate: Option<i64>,
+    pub frame_size: Option<(u32, u32)>,
+    pub video_tracks: Vec<Track>,
+    pub audio_tracks: Vec<Track>,
+    pub markers: Vec<Marker>,
+}
+
+#[derive(Debug, Default)]
+pub struct Track {
+    pub items: Vec<TrackItem>,
+}
+
+#[derive(Debug)]
+pub struct TrackItem {
+    pub name: String,
+    /// Timeline position in ticks
+    pub start: i64,
+    pub end: i64,
+    /// Source in/out points in ticks
+    pub in_point: Option<i64>,
+    pub out_point: Option<i64>,
+    pub source: Option<ClipSource>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Marker {
+    pub name: String,
+    pub comment: String,
+    pub kind: String,
+    pub start: i64,
+    pub duration: i64,
+}
+
+impl Sequence {
+    /// End of the last item on any track, in ticks
+    pub fn duration(&self) -> i64 {
+        self.video_tracks
+            .iter()
+            .chain(self.audio_tracks.iter())
+            .flat_map(|t| t.items.iter())
+            .map(|i| i.end)
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+impl Media {
+    /// Length in ticks, from the video stream or else the audio stream
+    pub fn duration(&self) -> Option<i64> {
+        [&self.video, &self.audio].into_iter().flatten().find_map(|s| s.duration)
+    }
+}
+
+impl Bin {
+    /// Every project item in this bin and the bins below it, with its
+    /// '/'-separated path from here ("Footage/Day 1/A001.mov")
+    pub fn item_paths(&self) -> Vec<(String, &ProjectItem)> {
+        let mut out = Vec::new();
+        self.collect_item_paths("", &mut out);
+        out
+    }
+
+    fn collect_item_paths<'b>(&'b self, prefix: &str, out: &mut Vec<(String, &'b ProjectItem)>) {
+        out.extend(self.items.iter().map(|item| (format!("{}{}", prefix, item.name), item)));
+        for bin in &self.bins {
+            bin.collect_item_paths(&format!("{}{}/", prefix, bin.name), out);
+        }
+    }
+}
+
+impl Project {
+    /// Parse a .prproj (gzip-compressed or plain XML) into the typed model
+    pub fn load(path: &Path, max_size_bytes: Option<usize>) -> Result<Project, io::Error> {
+        if let Some(max_bytes) = max_size_bytes {
+            let metadata = fs::metadata(path)?;
+            if metadata.len() > max_bytes as u64 {
+                return Err(io::Error::other(FileTooLarge { size: metadata.len(), limit: max_bytes as u64 }));
+            }
+        }
+        let doc = XmlNode::parse(BufReader::new(open_project_reader(path)?))?;
+        let mut project = Graph::new(&doc).build();
+        project.path = path.to_path_buf();
+        Ok(project)
+    }
+
+    pub fn media_for(&self, source: Option<ClipSource>) -> Option<&Media> {
+        match source {
+            Some(ClipSource::Media(i)) => self.media.get(i),
+            _ => None,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Minimal owned DOM
+
+#[derive(Debug, Default)]
+struct XmlNode {
+    name: String,
+    attrs: Vec<(String, String)>,
+    text: String,
+    children: Vec<XmlNode>,
+}
+
+impl XmlNode {
+    fn from_start(e: &BytesStart) -> XmlNode {
+        let attrs = e
+            .attributes()
+            .with_checks(false)
+            .flatten()
+            .map(|a| {
+                let key = String::from_utf8_lossy(a.key.as_ref()).to_string();
+                let val = a
+                    .unescape_value()
+                    .map(|v| v.to_string())
+                    .unwrap_or_else(|_| String::from_utf8_lossy(&a.value).to_string());
+                (key, val)
+            })
+            .collect();
+        XmlNode {
+            name: String::from_utf8_lossy(e.name().as_ref()).to_string(),
+            attrs,
+            ..Default::default()
+        }
+    }
+
+    fn parse<R: io::BufRead>(input: R) -> Result<XmlNode, io::Error> {
+        let mut reader = Reader::from_reader(input);
+        reader.trim_text(true);
+        let mut buf = Vec::new();
+        // Synthetic document node at the bottom of the stack
+        let mut stack = vec![XmlNode::default()];
+
+        loop {
+            match reader.read_event_into(&mut buf) {
+                Ok(Event::Start(e)) => stack.push(XmlNode::from_start(&e)),
+                Ok(Event::Empty(e)) => {
+                    let node = XmlNode::from_start(&e);
+                    stack.last_mut().expect("document node").children.push(node);
+                }
+                Ok(Event::Text(t)) => {
+                    if let Ok(text) = t.unescape() {
+                        stack.last_mut().expect("document node").text.push_str(&text);
+                    }
+                }
+                Ok(Event::CData(c)) => {
+                    let text = String::from_utf8_lossy(&c.into_inner()).to_string();
+                    stack.last_mut().expect("document node").text.push_str(&text);
+                }
+                Ok(Event::End(_)) if stack.len() > 1 => {
+                    let node = stack.pop().expect("open element");
+                    stack.last_mut().expect("document node").children.push(node);
+                }
+                Ok(Event::Eof) => break,
+                Err(e) => return Err(io::Error::new(io::ErrorKind::InvalidData, e)),
+                _ => {}
+            }
+            buf.clear();
+        }
+
+        // Close anything left open by a truncated file
+        while stack.len() > 1 {
+            let node = stack.pop().expect("open element");
+            stack.last_mut().expect("document node").children.push(node);
+        }
+        Ok(stack.pop().expect("document node"))
+    }
+
+    fn attr(&self, key: &str) -> Option<&str> {
+        self.attrs.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str())
+    }
+
+    fn child(&self, name: &str) -> Option<&XmlNode> {
+        self.children.iter().find(|c| c.name == name)
+    }
+
+    fn children_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a XmlNode> + 'a {
+        self.children.iter().filter(move |c| c.name == name)
+    }
+
+    /// Follow a chain of child element names
+    fn at(&self, path: &[&str]) -> Option<&XmlNode> {
+        path.iter().try_fold(self, |node, name| node.child(name))
+    }
+
+    fn text_at(&self, path: &[&str]) -> Option<&str> {
+        self.at(path).map(|n| n.text.trim()).filter(|t| !t.is_empty())
+    }
+
+    fn i64_at(&self, path: &[&str]) -> Option<i64> {
+        self.text_at(path).and_then(|t| t.parse().ok())
+    }
+
+    /// The ObjectID/ObjectUID this element points at, if it is a reference
+    fn reference(&self) -> Option<&str> {
+        self.attr("ObjectRef").or_else(|| self.attr("ObjectURef"))
+    }
+}
+
+fn parse_frame_rect(text: &str) -> Option<(u32, u32)> {
+    // "left,top,right,bottom"
+    let parts: Vec<i64> = text.split(',').filter_map(|p| p.trim().parse().ok()).collect();
+    if parts.len() == 4 {
+        Some(((parts[2] - parts[0]).max(0) as u32, (parts[3] - parts[1]).max(0) as u32))
+    } else {
+        None
+    }
+}
+
+// Marker payload: {"DVAMarker":{"mName":..,"mComment":..,"mType":..,"mStartTime":{"ticks":..},"mDuration":{"ticks":..}}}
+fn parse_marker(raw: &str) -> Option<Marker> {
+    let value: serde_json::Value = serde_json::from_str(raw).ok()?;
+    let m = &value["DVAMarker"];
+    let ticks = |key: &str| -> i64 {
+        let v = &m[key]["ticks"];
+        v.as_i64().or_else(|| v.as_str().and_then(|s| s.parse().ok())).unwrap_or(0)
+    };
+    let text = |key: &str| m[key].as_str().unwrap_or_default().to_string();
+    Some(Marker {
+        name: text("mName"),
+        comment: text("mComment"),
+        kind: text("mType"),
+        start: ticks("mStartTime"),
+        duration: ticks("mDuration"),
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Object graph resolution
+
+struct Graph<'a> {
+    objects: HashMap<&'a str, &'a XmlNode>,
+    media_index: HashMap<&'a str, usize>,
+    sequence_index: HashMap<&'a str, usize>,
+    master_index: HashMap<&'a str, usize>,
+    top_level: &'a [XmlNode],
+}
+
+impl<'a> Graph<'a> {
+    fn new(doc: &'a XmlNode) -> Graph<'a> {
+        let top_level: &[XmlNode] = doc
+            .child("PremiereData")
+            .map(|p| p.children.as_slice())
+            .unwrap_or(&[]);
+
+        let mut objects = HashMap::new();
+        let mut media_index = HashMap::new();
+        let mut sequence_index = HashMap::new();
+        let mut master_index = HashMap::new();
+        for node in top_level {
+            if let Some(id) = node.attr("ObjectID") {
+                objects.insert(id, node);
+            }
+            if let Some(uid) = node.attr("ObjectUID") {
+                objects.insert(uid, node);
+                let index = match node.name.as_str() {
+                    "Media" => Some(&mut media_index),
+                    "Sequence" => Some(&mut sequence_index),
+                    "MasterClip" => Some(&mut master_index),
+                    _ => None,
+                };
+                if let Some(index) = index {
+                    let next = index.len();
+                    index.insert(uid, next);
+                }
+            }
+        }
+
+        Graph { objects, media_index, sequence_index, master_index, top_level }
+    }
+
+    fn resolve(&self, node: &XmlNode) -> Option<&'a XmlNode> {
+        node.reference().and_then(|r| self.objects.get(r).copied())
+    }
+
+    fn resolve_at(&self, node: &XmlNode, path: &[&str]) -> Option<&'a XmlNode> {
+        node.at(path).and_then(|n| self.resolve(n))
+    }
+
+    fn build(&self) -> Project {
+        let mut project = Project::default();
+
+        for node in self.top_level {
+            match node.name.as_str() {
+                "Media" if node.attr("ObjectUID").is_some() => project.media.push(self.media(node)),
+                "MasterClip" if node.attr("ObjectUID").is_some() => {
+                    project.master_clips.push(self.master_clip(node))
+                }
+                "Sequence" if node.attr("ObjectUID").is_some() => {
+                    project.sequences.push(self.sequence(node))
+                }
+                _ => {}
+            }
+        }
+
+        // Sequence markers live on the clips of the master clip that wraps the sequence
+        for master in &project.master_clips {
+            for clip in &master.clips {
+                if let Some(ClipSource::Sequence(i)) = clip.source {
+                    if project.sequences[i].markers.is_empty() && !clip.markers.is_empty() {
+                        project.sequences[i].markers = clip.markers.clone();
+                    }
+                }
+            }
+        }
+
+        let root = self
+            .top_level
+            .iter()
+            .find(|n| n.name == "Project" && n.attr("ObjectID").is_some())
+            .and_then(|p| self.resolve_at(p, &["RootProjectItem"]));
+        if let Some(root) = root {
+            project.root = self.bin(root, &mut HashSet::from([std::ptr::from_ref(root)]));
+        }
+
+        project
+    }
+
+    fn media(&self, node: &XmlNode) -> Media {
+        let stream = |name: &str| {
+            node.child(name).and_then(|r| self.resolve(r)).map(|s| StreamInfo {
+                frame_rate: s.i64_at(&["FrameRate"]),
+                duration: s.i64_at(&["Duration"]),
+            })
+        };
+        Media {
+            uid: node.attr("ObjectUID").unwrap_or_default().to_string(),
+            title: node.text_at(&["Title"]).unwrap_or_default().to_string(),
+            file_path: node.text_at(&["FilePath"]).map(str::to_string),
+            actual_media_file_path: node.text_at(&["ActualMediaFilePath"]).map(str::to_string),
+            // Older projects carry a stale RelativePath before the current one
+            relative_path: node
+                .children_named("RelativePath")
+                .filter_map(|n| Some(n.text.trim()).filter(|t| !t.is_empty()))
+                .last()
+                .map(str::to_string),
+            start: if node.text_at(&["UseAlternateStart"]) == Some("true") {
+                node.i64_at(&["AlternateStart"])
+            } else {
+                node.i64_at(&["Start"])
+            },
+            video: stream("VideoStream"),
+            audio: stream("AudioStream"),
+        }
+    }
+
+    fn markers(&self, markers_ref: Option<&XmlNode>) -> Vec<Marker> {
+        let Some(markers) = markers_ref.and_then(|r| self.resolve(r)) else {
+            return Vec::new();
+        };
+        let Some(list) = markers.child("Markers") else {
+            return Vec::new();
+        };
+        let mut out: Vec<Marker> = list
+            .children_named("Marker")
+            .filter_map(|m| self.resolve_at(m, &["Second"]))
+            .filter_map(|m| m.text_at(&["DVAMarker"]).and_then(parse_marker))
+            .collect();
+        out.sort_by_key(|m| m.start);
+        out
+    }
+
+    fn clip(&self, node: &XmlNode) -> Clip {
+        let source = self.resolve_at(node, &["Clip", "Source"]).and_then(|src| {
+            if let Some(media) = self.resolve_at(src, &["MediaSource", "Media"]) {
+                let uid = media.attr("ObjectUID")?;
+                self.media_index.get(uid).map(|i| ClipSource::Media(*i))
+            } else {
+                let seq = self.resolve_at(src, &["SequenceSource", "Sequence"])?;
+                let uid = seq.attr("ObjectUID")?;
+                self.sequence_index.get(uid).map(|i| ClipSource::Sequence(*i))
+            }
+        });
+        Clip {
+            in_point: node.i64_at(&["Clip", "InPoint"]),
+            out_point: node.i64_at(&["Clip", "OutPoint"]),
+            source,
+            markers: self.markers(node.at(&["Clip", "MarkerOwner", "Markers"])),
+        }
+    }
+
+    fn master_clip(&self, node: &XmlNode) -> MasterClip {
+        let clips = node
+            .child("Clips")
+            .map(|c| {
+                c.children_named("Clip")
+                    .filter_map(|r| self.resolve(r))
+                    .map(|clip| self.clip(clip))
+                    .collect()
+            })
+            .unwrap_or_default();
+        MasterClip {
+            uid: node.attr("ObjectUID").unwrap_or_default().to_string(),
+            name: node.text_at(&["Name"]).unwrap_or_default().to_string(),
+            clips,
+        }
+    }
+
+    fn master_index_of(&self, node: Option<&XmlNode>) -> Option<usize> {
+        let uid = node?.attr("ObjectUID")?;
+        self.master_index.get(uid).copied()
+    }
+
+    fn track_item(&self, node: &XmlNode) -> TrackItem {
+        let sub_clip = self.resolve_at(node, &["ClipTrackItem", "SubClip"]);
+        let clip = sub_clip.and_then(|s| self.resolve_at(s, &["Clip"])).map(|c| self.clip(c));
+        TrackItem {
+            name: sub_clip
+                .and_then(|s| s.text_at(&["Name"]))
+                .unwrap_or_default()
+                .to_string(),
+            start: node.i64_at(&["ClipTrackItem", "TrackItem", "Start"]).unwrap_or(0),
+            end: node.i64_at(&["ClipTrackItem", "TrackItem", "End"]).unwrap_or(0),
+            in_point: clip.as_ref().and_then(|c| c.in_point),
+            out_point: clip.as_ref().and_then(|c| c.out_point),
+            source: clip.and_then(|c| c.source),
+        }
+    }
+
+    fn track(&self, node: &XmlNode) -> Track {
+        let clip_track = node.child("ClipTrack");
+        let items = clip_track
+            .and_then(|t| t.at(&["ClipItems", "TrackItems"]))
+            .map(|list| {
+                let mut items: Vec<TrackItem> = list
+                    .children_named("TrackItem")
+                    .filter_map(|r| self.resolve(r))
+                    .map(|item| self.track_item(item))
+                    .collect();
+                items.sort_by_key(|i| i.start);
+                items
+            })
+            .unwrap_or_default();
+        Track { items }
+    }
+
+    fn sequence(&self, node: &XmlNode) -> Sequence {
+        let mut seq = Sequence {
+            uid: node.attr("ObjectUID").unwrap_or_default().to_string(),
+            name: node.text_at(&["Name"]).unwrap_or_default().to_string(),
+            ..Default::default()
+        };
+
+        let groups = node.child("TrackGroups").map(|g| g.children_named("TrackGroup"));
+        for group in groups.into_iter().flatten() {
+            let Some(target) = self.resolve_at(group, &["Second"]) else {
+                continue;
+            };
+            let tracks: Vec<Track> = target
+                .at(&["TrackGroup", "Tracks"])
+                .map(|t| {
+                    t.children_named("Track")
+                        .filter_map(|r| self.resolve(r))
+                        .map(|track| self.track(track))
+                        .collect()
+                })
+                .unwrap_or_default();
+            match target.name.as_str() {
+                "VideoTrackGroup" => {
+                    seq.frame_rate = target.i64_at(&["TrackGroup", "FrameRate"]);
+                    seq.frame_size = target.text_at(&["FrameRect"]).and_then(parse_frame_rect);
+                    seq.video_tracks = tracks;
+                }
+                "AudioTrackGroup" => seq.audio_tracks = tracks,
+                _ => {}
+            }
+        }
+
+        seq
+    }
+
+    /// A bin and everything below it. `visited` holds the objects already placed
+    /// in the tree, so a bin that contains itself (directly or through another
+    /// bin) is only expanded once.
+    fn bin(&self, node: &XmlNode, visited: &mut HashSet<*const XmlNode>) -> Bin {
+        let mut bin = Bin {
+            name: node.text_at(&["ProjectItem", "Name"]).unwrap_or_default().to_string(),
+            ..Default::default()
+        };
+        let items = node
+            .at(&["ProjectItemContainer", "Items"])
+            .map(|i| i.children_named("Item"));
+        for item in items.into_iter().flatten().filter_map(|r| self.resolve(r)) {
+            if !visited.insert(std::ptr::from_ref(item)) {
+                continue;
+            }
+            if item.child("ProjectItemContainer").is_some() {
+                bin.bins.push(self.bin(item, visited));
+            } else {
+                bin.items.push(ProjectItem {
+                    name: item.text_at(&["ProjectItem", "Name"]).unwrap_or_default().to_string(),
+                    master_clip: self.master_index_of(self.resolve_at(item, &["MasterClip"])),
+                });
+            }
+        }
+        bin
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // One bin holding a clip, a bin that contains its parent, a dangling bin item,
+    // and a sequence whose track has one resolvable and one dangling track item
+    const FIXTURE: &str = r#"<?xml version="1.0" encoding="UTF-8" ?>
+<PremiereData Version="3">
+  <Project ObjectID="1"><RootProjectItem ObjectURef="root"/></Project>
+  <RootProjectItem ObjectUID="root">
+    <ProjectItem><Name>Root</Name></ProjectItem>
+    <ProjectItemContainer><Items>
+      <Item Index="0" ObjectURef="bin-footage"/>
+      <Item Index="1" ObjectURef="no-such-item"/>
+    </Items></ProjectItemContainer>
+  </RootProjectItem>
+  <BinProjectItem ObjectUID="bin-footage">
+    <ProjectItem><Name>Footage</Name></ProjectItem>
+    <ProjectItemContainer><Items>
+      <Item Index="0" ObjectURef="item-a001"/>
+      <Item Index="1" ObjectURef="bin-day1"/>
+    </Items></ProjectItemContainer>
+  </BinProjectItem>
+  <BinProjectItem ObjectUID="bin-day1">
+    <ProjectItem><Name>Day 1</Name></ProjectItem>
+    <ProjectItemContainer><Items>
+      <Item Index="0" ObjectURef="bin-footage"/>
+      <Item Index="1" ObjectURef="root"/>
+    </Items></ProjectItemContainer>
+  </BinProjectItem>
+  <ClipProjectItem ObjectUID="item-a001">
+    <ProjectItem><Name>A001.mov</Name></ProjectItem>
+    <MasterClip ObjectURef="master-a001"/>
+  </ClipProjectItem>
+  <MasterClip ObjectUID="master-a001">
+    <Name>A001.mov</Name>
+    <Clips><Clip Index="0" ObjectRef="10"/><Clip Index="1" ObjectRef="11"/></Clips>
+  </MasterClip>
+  <VideoClip ObjectID="10">
+    <Clip><Source ObjectRef="20"/><InPoint>0</InPoint><OutPoint>254016000000</OutPoint></Clip>
+  </VideoClip>
+  <VideoClip ObjectID="11"><Clip><Source ObjectRef="404"/></Clip></VideoClip>
+  <VideoMediaSource ObjectID="20">
+    <MediaSource><Media ObjectURef="media-a001"/></MediaSource>
+  </VideoMediaSource>
+  <Media ObjectUID="media-a001">
+    <VideoStream ObjectRef="60"/>
+    <AudioStream ObjectRef="61"/>
+    <FilePath>D:\Shoot\A001.mov</FilePath>
+    <Title>A001.mov</Title>
+    <Start>914457600000000</Start>
+  </Media>
+  <VideoStream ObjectID="60"><FrameRate>10594584000</FrameRate><Duration>2542704000000</Duration></VideoStream>
+  <AudioStream ObjectID="61"><FrameRate>5292000</FrameRate><Duration>2543616000000</Duration></AudioStream>
+  <Sequence ObjectUID="seq-1">
+    <Name>Cut 1</Name>
+    <TrackGroups><TrackGroup Index="0"><Second ObjectRef="30"/></TrackGroup></TrackGroups>
+  </Sequence>
+  <VideoTrackGroup ObjectID="30">
+    <TrackGroup><FrameRate>10594584000</FrameRate><Tracks><Track Index="0" ObjectURef="track-v1"/></Tracks></TrackGroup>
+  </VideoTrackGroup>
+  <VideoClipTrack ObjectUID="track-v1">
+    <ClipTrack><ClipItems><TrackItems>
+      <TrackItem Index="0" ObjectRef="41"/>
+      <TrackItem Index="1" ObjectRef="40"/>
+    </TrackItems></ClipItems></ClipTrack>
+  </VideoClipTrack>
+  <VideoClipTrackItem ObjectID="40">
+    <ClipTrackItem>
+      <TrackItem><Start>0</Start><End>254016000000</End></TrackItem>
+      <SubClip ObjectRef="50"/>
+    </ClipTrackItem>
+  </VideoClipTrackItem>
+  <SubClip ObjectID="50"><Name>A001 take</Name><Clip ObjectRef="10"/></SubClip>
+</PremiereData>
+"#;
+
+    fn load(xml: &str) -> Project {
+        let doc = XmlNode::parse(xml.as_bytes()).unwrap();
+        Graph::new(&doc).build()
+    }
+
+    #[test]
+    fn references_resolve_to_model_indices() {
+        let project = load(FIXTURE);
+        assert_eq!(project.media.len(), 1);
+        assert_eq!(project.media[0].uid, "media-a001");
+        assert_eq!(project.media[0].file_path.as_deref(), Some("D:\\Shoot\\A001.mov"));
+        let video = project.media[0].video.as_ref().unwrap();
+        assert_eq!(video.frame_rate, Some(10_594_584_000));
+        assert_eq!(project.media[0].audio.as_ref().unwrap().frame_rate, Some(5_292_000));
+        assert_eq!(project.media[0].duration(), Some(2_542_704_000_000));
+        assert_eq!(project.media[0].start, Some(914_457_600_000_000));
+
+        let master = &project.master_clips[0];
+        assert_eq!(master.name, "A001.mov");
+        assert_eq!(master.clips[0].source, Some(ClipSource::Media(0)));
+        assert_eq!(master.clips[0].out_point, Some(254_016_000_000));
+
+        let seq = &project.sequences[0];
+        assert_eq!(seq.frame_rate, Some(10_594_584_000));
+        let item = &seq.video_tracks[0].items[0];
+        assert_eq!(item.name, "A001 take");
+        assert_eq!(item.source, Some(ClipSource::Media(0)));
+        assert_eq!(item.end, 254_016_000_000);
+    }
+
+    #[test]
+    fn dangling_references_are_skipped() {
+        let project = load(FIXTURE);
+        // Clip 11 points at an object that does not exist
+        assert_eq!(project.master_clips[0].clips[1].source, None);
+        // Track item 41 does not exist; only item 40 is on the track
+        assert_eq!(project.sequences[0].video_tracks[0].items.len(), 1);
+        // The root's second item does not exist
+        assert_eq!(project.root.bins.len(), 1);
+        assert!(project.root.items.is_empty());
+    }
+
+    #[test]
+    fn cyclic_bins_are_expanded_once() {
+        let project = load(FIXTURE);
+        let footage = &project.root.bins[0];
+        assert_eq!(footage.name, "Footage");
+        assert_eq!(footage.items[0].master_clip, Some(0));
+        // "Day 1" lists its parent and the root again; neither is expanded a second time
+        let day1 = &footage.bins[0];
+        assert_eq!(day1.name, "Day 1");
+        assert!(day1.bins.is_empty() && day1.items.is_empty());
+
+        let paths: Vec<String> = project.root.item_paths().into_iter().map(|(path, _)| path).collect();
+        assert_eq!(paths, vec!["Footage/A001.mov"]);
+    }
+
+    #[test]
+    fn self_referencing_bin() {
+        let xml = r#"<PremiereData>
+  <Project ObjectID="1"><RootProjectItem ObjectRef="2"/></Project>
+  <RootProjectItem ObjectID="2">
+    <ProjectItemContainer><Items><Item ObjectRef="2"/><Item ObjectRef="3"/></Items></ProjectItemContainer>
+  </RootProjectItem>
+  <BinProjectItem ObjectID="3">
+    <ProjectItem><Name>Loop</Name></ProjectItem>
+    <ProjectItemContainer><Items><Item ObjectRef="3"/></Items></ProjectItemContainer>
+  </BinProjectItem>
+</PremiereData>"#;
+        let project = load(xml);
+        assert_eq!(project.root.bins.len(), 1);
+        assert_eq!(project.root.bins[0].name, "Loop");
+        assert!(project.root.bins[0].bins.is_empty());
+    }
+
+    #[test]
+    fn timecode_from_ticks() {
+        assert_eq!(ticks_to_timecode(0, 0), "00:00:00:00");
+        assert_eq!(ticks_to_timecode(TICKS_PER_SECOND * 3661, TICKS_PER_SECOND / 25), "01:01:01:00");
+        assert_eq!(ticks_to_timecode(-5, TICKS_PER_SECOND / 25), "00:00:00:00");
+    }
+    #[test]
+    fn modified_timecode_overrides_the_file_start() {
+        let xml = r#"<PremiereData>
+  <Media ObjectUID="m1"><Start>100</Start><AlternateStart>200</AlternateStart><UseAlternateStart>true</UseAlternateStart></Media>
+  <Media ObjectUID="m2"><Start>100</Start><AlternateStart>200</AlternateStart><UseAlternateStart>false</UseAlternateStart></Media>
+  <Media ObjectUID="m3"><Title>No timecode</Title></Media>
+</PremiereData>"#;
+        let project = load(xml);
+        let starts: Vec<Option<i64>> = project.media.iter().map(|m| m.start).collect();
+        assert_eq!(starts, vec![Some(200), Some(100), None]);
+    }
+}
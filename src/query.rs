@@ -0,0 +1,464 @@
+// Search text matching: plain substring / regex matchers and boolean queries
+// (AND / OR / NOT) evaluated over a single streaming pass.
+
+use memchr::{memmem, memrchr};
+use regex::{bytes, Regex, RegexBuilder};
+
+/// Longest stretch of the current line carried between chunks for regex terms
+const MAX_LINE_CARRY: usize = 64 * 1024;
+
+/// How a single search term is matched against the project text.
+pub enum Matcher {
+    /// Case-insensitive substring; holds the lowercased ASCII needle
+    Literal(String),
+    /// Case-insensitive regular expression, or an escaped literal with non-ASCII
+    /// characters, so Unicode case folding is the same for every way of matching
+    Regex(Regex),
+}
+
+impl Matcher {
+    pub fn new(search_text: &str, use_regex: bool) -> Result<Matcher, regex::Error> {
+        if !use_regex && search_text.is_ascii() {
+            return Ok(Matcher::Literal(search_text.to_ascii_lowercase()));
+        }
+        let pattern = if use_regex { search_text.to_string() } else { regex::escape(search_text) };
+        let re = RegexBuilder::new(&pattern).case_insensitive(true).build()?;
+        Ok(Matcher::Regex(re))
+    }
+
+    pub fn is_match(&self, haystack: &str) -> bool {
+        match self {
+            Matcher::Literal(needle) => haystack.to_lowercase().contains(needle.as_str()),
+            Matcher::Regex(re) => re.is_match(haystack),
+        }
+    }
+
+    /// Byte ranges of every non-overlapping match in `haystack`
+    pub fn find_all(&self, haystack: &str) -> Vec<(usize, usize)> {
+        match self {
+            Matcher::Literal(needle) => {
+                if needle.is_empty() {
+                    return Vec::new();
+                }
+                // ASCII lowercasing keeps byte offsets, so ranges index `haystack` directly
+                haystack
+                    .to_ascii_lowercase()
+                    .match_indices(needle.as_str())
+                    .map(|(pos, _)| (pos, pos + needle.len()))
+                    .collect()
+            }
+            Matcher::Regex(re) => re.find_iter(haystack).map(|m| (m.start(), m.end())).collect(),
+        }
+    }
+}
+
+/// Byte-level form of a term for the streaming file search. It runs on raw chunks
+/// of the project, so invalid UTF-8 and very long lines need no special handling.
+pub enum BytePattern {
+    /// ASCII needle, found with a SIMD substring search in ASCII-lowercased text
+    Folded(Box<memmem::Finder<'static>>),
+    /// Regex, or a literal with non-ASCII characters that needs Unicode case folding
+    Regex(bytes::Regex),
+}
+
+impl BytePattern {
+    pub fn new(search_text: &str, use_regex: bool) -> Result<BytePattern, regex::Error> {
+        if !use_regex && search_text.is_ascii() {
+            let needle = search_text.to_ascii_lowercase();
+            return Ok(BytePattern::Folded(Box::new(memmem::Finder::new(needle.as_bytes()).into_owned())));
+        }
+        let pattern = if use_regex { search_text.to_string() } else { regex::escape(search_text) };
+        // Chunks hold many lines; ^ and $ still anchor at line boundaries
+        let re = bytes::RegexBuilder::new(&pattern)
+            .case_insensitive(true)
+            .multi_line(true)
+            .crlf(true)
+            .build()?;
+        Ok(BytePattern::Regex(re))
+    }
+
+    /// Byte range of the first match in `text`; `folded` is `text` with ASCII letters lowercased
+    pub fn find(&self, text: &[u8], folded: &[u8]) -> Option<(usize, usize)> {
+        match self {
+            BytePattern::Folded(finder) => finder.find(folded).map(|pos| (pos, pos + finder.needle().len())),
+            BytePattern::Regex(re) => re.find(text).map(|m| (m.start(), m.end())),
+        }
+    }
+
+    /// Trailing bytes of `window` to search again with the next chunk: enough for a
+    /// literal to straddle the boundary, or the unfinished line for a regex
+    pub fn carry(&self, window: &[u8]) -> usize {
+        match self {
+            BytePattern::Folded(finder) => finder.needle().len().saturating_sub(1),
+            BytePattern::Regex(_) => {
+                let line_start = memrchr(b'\n', window).map_or(0, |i| i + 1);
+                (window.len() - line_start).min(MAX_LINE_CARRY)
+            }
+        }
+    }
+}
+
+enum Expr {
+    Term(usize),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+}
+
+/// A parsed SEARCH_TEXT. Plain text is a single term; text containing the
+/// standalone keywords AND, OR or NOT is parsed as a boolean expression.
+pub struct Query {
+    pub terms: Vec<Matcher>,
+    /// Byte-level form of each term for the streaming file search, parallel to `terms`
+    pub patterns: Vec<BytePattern>,
+    /// Display form of each term, parallel to `terms`
+    pub labels: Vec<String>,
+    /// True for terms that only appear under an odd number of NOTs
+    negated: Vec<bool>,
+    expr: Expr,
+    use_regex: bool,
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Word(String),
+    Quoted(String),
+    And,
+    Or,
+    Not,
+    LParen,
+    RParen,
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '(' {
+            chars.next();
+            tokens.push(Token::LParen);
+        } else if c == ')' {
+            chars.next();
+            tokens.push(Token::RParen);
+        } else if c == '"' {
+            chars.next();
+            let mut term = String::new();
+            let mut closed = false;
+            for c in chars.by_ref() {
+                if c == '"' {
+                    closed = true;
+                    break;
+                }
+                term.push(c);
+            }
+            if !closed {
+                return Err("unterminated quote".to_string());
+            }
+            tokens.push(Token::Quoted(term));
+        } else {
+            let mut word = String::new();
+            while let Some(&c) = chars.peek() {
+                if c.is_whitespace() || c == '(' || c == ')' || c == '"' {
+                    break;
+                }
+                word.push(c);
+                chars.next();
+            }
+            tokens.push(match word.as_str() {
+                "AND" => Token::And,
+                "OR" => Token::Or,
+                "NOT" => Token::Not,
+                _ => Token::Word(word),
+            });
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    labels: Vec<String>,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.parse_and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.pos += 1;
+            let rhs = self.parse_and()?;
+            lhs = Expr::Or(Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.parse_unary()?;
+        while self.peek() == Some(&Token::And) {
+            self.pos += 1;
+            let rhs = self.parse_unary()?;
+            lhs = Expr::And(Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, String> {
+        if self.peek() == Some(&Token::Not) {
+            self.pos += 1;
+            let inner = self.parse_unary()?;
+            return Ok(Expr::Not(Box::new(inner)));
+        }
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr, String> {
+        match self.tokens.get(self.pos) {
+            Some(Token::LParen) => {
+                self.pos += 1;
+                let inner = self.parse_or()?;
+                if self.peek() != Some(&Token::RParen) {
+                    return Err("expected ')'".to_string());
+                }
+                self.pos += 1;
+                Ok(inner)
+            }
+            Some(Token::Quoted(term)) => {
+                let term = term.clone();
+                self.pos += 1;
+                Ok(self.push_term(term))
+            }
+            Some(Token::Word(_)) => {
+                // Consecutive bare words form a single phrase: clair de lune AND final mix
+                let mut words = Vec::new();
+                while let Some(Token::Word(w)) = self.tokens.get(self.pos) {
+                    words.push(w.clone());
+                    self.pos += 1;
+                }
+                Ok(self.push_term(words.join(" ")))
+            }
+            Some(tok) => Err(format!("unexpected {:?}", tok)),
+            None => Err("unexpected end of query".to_string()),
+        }
+    }
+
+    fn push_term(&mut self, term: String) -> Expr {
+        self.labels.push(term);
+        Expr::Term(self.labels.len() - 1)
+    }
+}
+
+fn mark_negated(expr: &Expr, negated: bool, out: &mut Vec<bool>) {
+    match expr {
+        Expr::Term(i) => out[*i] = negated,
+        Expr::Not(inner) => mark_negated(inner, !negated, out),
+        Expr::And(a, b) | Expr::Or(a, b) => {
+            mark_negated(a, negated, out);
+            mark_negated(b, negated, out);
+        }
+    }
+}
+
+// Three-valued evaluation: a term not seen yet is unknown until the whole file has been read
+fn eval(expr: &Expr, found: &[bool], complete: bool) -> Option<bool> {
+    match expr {
+        Expr::Term(i) => {
+            if found[*i] {
+                Some(true)
+            } else if complete {
+                Some(false)
+            } else {
+                None
+            }
+        }
+        Expr::Not(inner) => eval(inner, found, complete).map(|v| !v),
+        Expr::And(a, b) => match (eval(a, found, complete), eval(b, found, complete)) {
+            (Some(false), _) | (_, Some(false)) => Some(false),
+            (Some(true), Some(true)) => Some(true),
+            _ => None,
+        },
+        Expr::Or(a, b) => match (eval(a, found, complete), eval(b, found, complete)) {
+            (Some(true), _) | (_, Some(true)) => Some(true),
+            (Some(false), Some(false)) => Some(false),
+            _ => None,
+        },
+    }
+}
+
+/// Whether the text uses AND / OR / NOT as standalone words. Checked before tokenizing,
+/// so quotes in plain text ('12" vinyl') or in regexes never make a search fail.
+fn has_keywords(text: &str) -> bool {
+    text.split(|c: char| c.is_whitespace() || matches!(c, '(' | ')' | '"'))
+        .any(|word| matches!(word, "AND" | "OR" | "NOT"))
+}
+
+/// The phrase inside a search text that is one quoted phrase: "rock AND roll"
+fn single_quoted(text: &str) -> Option<&str> {
+    let inner = text.trim().strip_prefix('"')?.strip_suffix('"')?;
+    (!inner.contains('"')).then_some(inner)
+}
+
+impl Query {
+    pub fn parse(text: &str, use_regex: bool) -> Result<Query, String> {
+        let tokens = if has_keywords(text) { tokenize(text)? } else { Vec::new() };
+        let is_boolean = tokens
+            .iter()
+            .any(|t| matches!(t, Token::And | Token::Or | Token::Not));
+
+        let (expr, labels) = if is_boolean {
+            let mut parser = Parser { tokens, pos: 0, labels: Vec::new() };
+            let expr = parser.parse_or()?;
+            if let Some(tok) = parser.peek() {
+                return Err(format!("unexpected {:?}", tok));
+            }
+            (expr, parser.labels)
+        } else if let Some(term) = single_quoted(text) {
+            // A single quoted phrase lets the keywords themselves be searched: "rock AND roll"
+            (Expr::Term(0), vec![term.to_string()])
+        } else {
+            // Plain search text is matched verbatim, parentheses included
+            (Expr::Term(0), vec![text.to_string()])
+        };
+
+        let mut terms = Vec::with_capacity(labels.len());
+        let mut patterns = Vec::with_capacity(labels.len());
+        for label in &labels {
+            if label.is_empty() {
+                return Err("empty search term".to_string());
+            }
+            terms.push(Matcher::new(label, use_regex).map_err(|e| e.to_string())?);
+            patterns.push(BytePattern::new(label, use_regex).map_err(|e| e.to_string())?);
+        }
+
+        let mut negated = vec![false; labels.len()];
+        mark_negated(&expr, false, &mut negated);
+
+        Ok(Query { terms, patterns, labels, negated, expr, use_regex })
+    }
+
+    pub fn is_boolean(&self) -> bool {
+        !matches!(self.expr, Expr::Term(_))
+    }
+
+    /// Whether term `idx` contributes a positive hit (i.e. is not under NOT)
+    pub fn is_positive(&self, idx: usize) -> bool {
+        !self.negated[idx]
+    }
+
+    /// Whether every match of every term lies within one line, so the query can be
+    /// answered line by line (a regex may match across line breaks)
+    pub fn matches_within_lines(&self) -> bool {
+        !self.use_regex && !self.labels.iter().any(|l| l.contains('\n'))
+    }
+
+    /// Trailing bytes of `window` the streaming search must carry into the next chunk
+    pub fn carry(&self, window: &[u8]) -> usize {
+        self.patterns.iter().map(|p| p.carry(window)).max().unwrap_or(0)
+    }
+
+    /// Evaluate with the terms found so far. Returns None while the outcome
+    /// still depends on text not read yet; `complete` marks end of input.
+    pub fn evaluate(&self, found: &[bool], complete: bool) -> Option<bool> {
+        eval(&self.expr, found, complete)
+    }
+
+    /// Evaluate the whole query against a single piece of text
+    pub fn matches_text(&self, text: &str) -> bool {
+        let found: Vec<bool> = self.terms.iter().map(|m| m.is_match(text)).collect();
+        self.evaluate(&found, true).unwrap_or(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(text: &str) -> Query {
+        Query::parse(text, false).unwrap()
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        let q = query("alpha OR beta AND gamma");
+        assert!(q.matches_text("alpha"));
+        assert!(!q.matches_text("beta"));
+        assert!(q.matches_text("beta gamma"));
+    }
+
+    #[test]
+    fn parentheses_override_precedence() {
+        let q = query("(alpha OR beta) AND gamma");
+        assert!(!q.matches_text("alpha"));
+        assert!(q.matches_text("alpha gamma"));
+        assert!(q.matches_text("beta gamma"));
+    }
+
+    #[test]
+    fn not_negates_and_marks_terms() {
+        let q = query("alpha AND NOT beta");
+        assert!(q.matches_text("alpha"));
+        assert!(!q.matches_text("alpha beta"));
+        assert!(q.is_positive(0));
+        assert!(!q.is_positive(1));
+
+        let q = query("NOT NOT alpha");
+        assert!(q.matches_text("alpha"));
+        assert!(!q.matches_text("beta"));
+        assert!(q.is_positive(0));
+    }
+
+    #[test]
+    fn unknown_until_complete() {
+        let q = query("alpha AND NOT beta");
+        assert_eq!(q.evaluate(&[true, false], false), None);
+        assert_eq!(q.evaluate(&[true, false], true), Some(true));
+        assert_eq!(q.evaluate(&[true, true], false), Some(false));
+        assert_eq!(q.evaluate(&[false, false], false), None);
+    }
+
+    #[test]
+    fn bare_words_form_one_phrase() {
+        let q = query("clair de lune AND final mix");
+        assert_eq!(q.labels, vec!["clair de lune", "final mix"]);
+    }
+
+    #[test]
+    fn quotes_protect_keywords() {
+        let q = query("\"rock AND roll\"");
+        assert!(!q.is_boolean());
+        assert_eq!(q.labels, vec!["rock AND roll"]);
+
+        let q = query("\"rock AND roll\" OR jazz");
+        assert_eq!(q.labels, vec!["rock AND roll", "jazz"]);
+        assert!(q.matches_text("Rock and Roll"));
+    }
+
+    #[test]
+    fn plain_text_is_matched_verbatim() {
+        let q = query("(draft) OR-ish");
+        assert!(!q.is_boolean());
+        assert_eq!(q.labels, vec!["(draft) OR-ish"]);
+    }
+
+    #[test]
+    fn unbalanced_quote_in_plain_text_or_regex() {
+        let q = query("12\" vinyl");
+        assert_eq!(q.labels, vec!["12\" vinyl"]);
+        assert!(q.matches_text("a 12\" Vinyl pressing"));
+
+        let q = Query::parse("Path>\"?D:", true).unwrap();
+        assert!(q.matches_text("<FilePath>D:\\Shoot"));
+    }
+
+    #[test]
+    fn unbalanced_quote_in_boolean_query_is_an_error() {
+        assert!(Query::parse("alpha AND \"beta", false).is_err());
+        assert!(Query::parse("alpha AND", false).is_err());
+        assert!(Query::parse("(alpha OR beta", false).is_err());
+    }
+}
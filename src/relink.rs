@@ -0,0 +1,362 @@
+// Bulk relink (`relink` subcommand): look up the media a project reports missing
+// by file name under one or more media roots, optionally keeping only files as
+// long as the project says the media was, propose a relink map per project, and
+// optionally write relinked copies through the same rewrite as `remap`.
+
+use clap::Args;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
+use std::sync::Mutex;
+
+use crate::discover::{discover_files, DiscoverOptions};
+use crate::media::{check_project_media, file_name_of, MediaStatus};
+use crate::probe::media_duration;
+use crate::project::{Media, Project, TICKS_PER_SECOND};
+use crate::remap::{print_changes, rewrite_media_paths, same_file, sibling_output};
+use crate::rules::PathRules;
+use crate::{normalize_asset_path, ASSET_EXTENSIONS};
+
+#[derive(Args, Debug)]
+pub struct RelinkArgs {
+    /// Projects to check, or folders to search for projects
+    #[arg(value_name = "PROJECT", required = true)]
+    pub projects: Vec<PathBuf>,
+
+    /// Folder to search for the missing media (repeatable)
+    #[arg(long = "media-root", value_name = "DIR", required = true)]
+    pub media_roots: Vec<PathBuf>,
+
+    /// Use the first copy of a file name found in several places when every copy has
+    /// the same size (copies are compared with each other, not with the original)
+    #[arg(long, default_value_t = false)]
+    pub accept_identical_duplicates: bool,
+
+    /// Only propose files whose duration matches the one the project recorded for the
+    /// missing media (QuickTime/MP4 and WAV files are read; other formats are kept)
+    #[arg(long, default_value_t = false)]
+    pub match_duration: bool,
+
+    /// Write a relinked copy of each project as <name>.relinked.prproj next to it
+    #[arg(long, default_value_t = false)]
+    pub apply: bool,
+}
+
+/// A media file found under a media root
+struct Candidate {
+    path: PathBuf,
+    size: u64,
+    /// Length in ticks, when probed and readable
+    duration: Option<i64>,
+}
+
+/// What the media roots offer for one missing asset
+#[derive(Debug, PartialEq)]
+enum Proposal {
+    Found(String),
+    Ambiguous(Vec<String>),
+    /// Files with the name exist, but none is as long as the original
+    OtherDuration(Vec<String>),
+    NotFound,
+}
+
+/// Length the project recorded for a media file, with how far a file's length may
+/// differ from it: two frames of the media's video, and at least a tenth of a second
+struct Duration {
+    ticks: i64,
+    tolerance: i64,
+}
+
+impl Duration {
+    fn of(media: &Media) -> Option<Duration> {
+        let frame = media.video.as_ref().and_then(|v| v.frame_rate).unwrap_or(0);
+        Some(Duration { ticks: media.duration()?, tolerance: (2 * frame).max(TICKS_PER_SECOND / 10) })
+    }
+
+    fn matches(&self, ticks: i64) -> bool {
+        (ticks - self.ticks).abs() <= self.tolerance
+    }
+}
+
+fn discover_options(extensions: Vec<String>) -> DiscoverOptions {
+    DiscoverOptions {
+        follow_links: false,
+        exclude_dirs: None,
+        extensions,
+        min_size_bytes: None,
+        modified_after: None,
+        modified_before: None,
+        max_depth: None,
+        rules: PathRules::default(),
+        walk_threads: 0,
+    }
+}
+
+/// Project files given directly, plus those found under the given folders
+fn project_files(inputs: &[PathBuf]) -> Vec<PathBuf> {
+    let (files, dirs): (Vec<PathBuf>, Vec<PathBuf>) = inputs.iter().cloned().partition(|p| p.is_file());
+    let found = Mutex::new(files);
+    discover_files(&dirs, &discover_options(vec!["prproj".to_string()]), &AtomicBool::new(false), |path| {
+        found.lock().unwrap().push(path);
+        true
+    });
+    let mut projects = found.into_inner().unwrap();
+    projects.sort();
+    projects.dedup();
+    projects
+}
+
+/// Recorded duration of each media file of a project, keyed by its lowercased
+/// normalized paths (as `check_project_media` reports them)
+fn recorded_durations(project: &Path) -> HashMap<String, Duration> {
+    let mut map = HashMap::new();
+    // Best effort: without the model, candidates are matched by name only
+    let Ok(project) = Project::load(project, None) else {
+        return map;
+    };
+    for media in &project.media {
+        for path in [&media.file_path, &media.actual_media_file_path, &media.relative_path].into_iter().flatten() {
+            if let Some(duration) = Duration::of(media) {
+                map.insert(normalize_asset_path(path).to_lowercase(), duration);
+            }
+        }
+    }
+    map
+}
+
+/// Media files under the roots whose (lowercased) name is in `wanted`, by name.
+/// With `probe`, each file's duration is read too.
+fn index_media(roots: &[PathBuf], wanted: &HashSet<String>, probe: bool) -> HashMap<String, Vec<Candidate>> {
+    let extensions = ASSET_EXTENSIONS.iter().map(|e| e.to_string()).collect();
+    let index: Mutex<HashMap<String, Vec<Candidate>>> = Mutex::new(HashMap::new());
+    discover_files(roots, &discover_options(extensions), &AtomicBool::new(false), |path| {
+        let name = path.file_name().map(|n| n.to_string_lossy().to_lowercase()).unwrap_or_default();
+        if wanted.contains(&name) {
+            let size = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+            let duration = if probe { media_duration(&path) } else { None };
+            index.lock().unwrap().entry(name).or_default().push(Candidate { path, size, duration });
+        }
+        true
+    });
+    let mut index = index.into_inner().unwrap();
+    for candidates in index.values_mut() {
+        candidates.sort_by(|a, b| a.path.cmp(&b.path));
+    }
+    index
+}
+
+/// With `duration`, candidates of another length are left out; those whose length
+/// can't be read stay in
+fn propose(candidates: Option<&Vec<Candidate>>, duration: Option<&Duration>, accept_duplicates: bool) -> Proposal {
+    let Some(named) = candidates.filter(|c| !c.is_empty()) else {
+        return Proposal::NotFound;
+    };
+    let candidates: Vec<&Candidate> = named
+        .iter()
+        .filter(|c| match (duration, c.duration) {
+            (Some(expected), Some(actual)) => expected.matches(actual),
+            _ => true,
+        })
+        .collect();
+    if candidates.is_empty() {
+        return Proposal::OtherDuration(named.iter().map(|c| c.path.display().to_string()).collect());
+    }
+    let same_size = candidates.iter().all(|c| c.size == candidates[0].size);
+    if candidates.len() == 1 || (accept_duplicates && same_size) {
+        Proposal::Found(candidates[0].path.display().to_string())
+    } else {
+        Proposal::Ambiguous(candidates.iter().map(|c| c.path.display().to_string()).collect())
+    }
+}
+
+/// Run the `relink` subcommand; returns the process exit code
+/// (1 when some missing media could not be relinked, or on errors)
+pub fn run(args: &RelinkArgs) -> i32 {
+    let projects = project_files(&args.projects);
+    if projects.is_empty() {
+        eprintln!("Error: no projects found");
+        return 1;
+    }
+
+    // Missing assets per project
+    let mut missing: Vec<(PathBuf, Vec<String>)> = Vec::new();
+    let mut failed = false;
+    for project in projects {
+        match check_project_media(&project, None) {
+            Ok(checks) => {
+                let assets: Vec<String> = checks
+                    .into_iter()
+                    .filter(|c| c.status == MediaStatus::Missing)
+                    .map(|c| c.asset)
+                    .collect();
+                missing.push((project, assets));
+            }
+            Err(e) => {
+                eprintln!("Error: cannot read {}: {}", project.display(), e);
+                failed = true;
+            }
+        }
+    }
+
+    let wanted: HashSet<String> = missing
+        .iter()
+        .flat_map(|(_, assets)| assets.iter().map(|a| file_name_of(a).to_lowercase()))
+        .collect();
+    let index = if wanted.is_empty() {
+        HashMap::new()
+    } else {
+        index_media(&args.media_roots, &wanted, args.match_duration)
+    };
+
+    let (mut relinkable, mut ambiguous, mut not_found) = (0, 0, 0);
+    for (project, assets) in &missing {
+        if assets.is_empty() {
+            println!("\n✓ {}: no missing media", project.display());
+            continue;
+        }
+
+        let durations = if args.match_duration { recorded_durations(project) } else { HashMap::new() };
+        let mut map: HashMap<String, String> = HashMap::new();
+        let mut block = format!("\n⚠ {}: {} missing", project.display(), assets.len());
+        for asset in assets {
+            let candidates = index.get(&file_name_of(asset).to_lowercase());
+            match propose(candidates, durations.get(&asset.to_lowercase()), args.accept_identical_duplicates) {
+                Proposal::Found(path) => {
+                    relinkable += 1;
+                    block.push_str(&format!("\n    {}\n      -> {}", asset, path));
+                    map.insert(asset.to_lowercase(), path);
+                }
+                Proposal::Ambiguous(paths) => {
+                    ambiguous += 1;
+                    block.push_str(&format!("\n    {}  (ambiguous: {} candidates)", asset, paths.len()));
+                    for path in paths {
+                        block.push_str(&format!("\n      ?  {}", path));
+                    }
+                }
+                Proposal::OtherDuration(paths) => {
+                    not_found += 1;
+                    block.push_str(&format!("\n    {}  (not found: no candidate of the same duration)", asset));
+                    for path in paths {
+                        block.push_str(&format!("\n      x  {}", path));
+                    }
+                }
+                Proposal::NotFound => {
+                    not_found += 1;
+                    block.push_str(&format!("\n    {}  (not found)", asset));
+                }
+            }
+        }
+        println!("{}", block);
+
+        if args.apply && !map.is_empty() {
+            let output = sibling_output(project, "relinked");
+            if same_file(project, &output) {
+                eprintln!("Error: {} would replace the original project", output.display());
+                failed = true;
+                continue;
+            }
+            match rewrite_media_paths(project, Some(&output), |path| map.get(&path.to_lowercase()).cloned()) {
+                Ok(report) => {
+                    println!("  Wrote {} ({} path values rewritten):", output.display(), report.values_changed());
+                    print_changes(&report);
+                }
+                Err(e) => {
+                    eprintln!("Error: cannot relink {}: {}", project.display(), e);
+                    failed = true;
+                }
+            }
+        }
+    }
+
+    println!("\n{}", "=".repeat(60));
+    println!("Projects checked: {}", missing.len());
+    println!("Missing media: {}", relinkable + ambiguous + not_found);
+    println!("Relinkable: {}", relinkable);
+    println!("Ambiguous: {}", ambiguous);
+    println!("Not found: {}", not_found);
+    if !args.apply && relinkable > 0 {
+        println!("Run again with --apply to write relinked copies");
+    }
+    println!("{}", "=".repeat(60));
+
+    if failed || ambiguous + not_found > 0 {
+        1
+    } else {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::StreamInfo;
+
+    fn candidate(path: &str, size: u64, duration: Option<i64>) -> Candidate {
+        Candidate { path: PathBuf::from(path), size, duration }
+    }
+
+    fn paths(paths: &[&str]) -> Vec<String> {
+        paths.iter().map(|p| p.to_string()).collect()
+    }
+
+    const SECOND: i64 = TICKS_PER_SECOND;
+
+    #[test]
+    fn single_candidate_is_found() {
+        let candidates = vec![candidate("/nas/A001.mov", 10, None)];
+        assert_eq!(propose(Some(&candidates), None, false), Proposal::Found("/nas/A001.mov".to_string()));
+    }
+
+    #[test]
+    fn no_candidates() {
+        assert_eq!(propose(None, None, true), Proposal::NotFound);
+        assert_eq!(propose(Some(&Vec::new()), None, true), Proposal::NotFound);
+    }
+
+    #[test]
+    fn repeated_names_are_ambiguous() {
+        let candidates = vec![candidate("/a/A001.mov", 10, None), candidate("/b/A001.mov", 10, None)];
+        assert_eq!(propose(Some(&candidates), None, false), Proposal::Ambiguous(paths(&["/a/A001.mov", "/b/A001.mov"])));
+    }
+
+    #[test]
+    fn identical_duplicates_can_be_accepted() {
+        let same = vec![candidate("/a/A001.mov", 10, None), candidate("/b/A001.mov", 10, None)];
+        assert_eq!(propose(Some(&same), None, true), Proposal::Found("/a/A001.mov".to_string()));
+
+        let different = vec![candidate("/a/A001.mov", 10, None), candidate("/b/A001.mov", 11, None)];
+        assert!(matches!(propose(Some(&different), None, true), Proposal::Ambiguous(_)));
+    }
+
+    #[test]
+    fn duration_narrows_the_candidates() {
+        let original = Duration { ticks: 60 * SECOND, tolerance: SECOND / 10 };
+        let candidates = vec![
+            candidate("/a/A001.mov", 10, Some(12 * SECOND)),
+            candidate("/b/A001.mov", 11, Some(60 * SECOND + SECOND / 20)),
+        ];
+        assert_eq!(propose(Some(&candidates), Some(&original), false), Proposal::Found("/b/A001.mov".to_string()));
+
+        // A file whose length can't be read is not ruled out
+        let unreadable = vec![candidate("/a/A001.mxf", 10, Some(12 * SECOND)), candidate("/b/A001.mxf", 11, None)];
+        assert_eq!(propose(Some(&unreadable), Some(&original), false), Proposal::Found("/b/A001.mxf".to_string()));
+
+        let none_match = vec![candidate("/a/A001.mov", 10, Some(12 * SECOND))];
+        assert_eq!(propose(Some(&none_match), Some(&original), false), Proposal::OtherDuration(paths(&["/a/A001.mov"])));
+    }
+
+    #[test]
+    fn tolerance_is_two_frames_or_a_tenth_of_a_second() {
+        let media = |frame_rate| Media {
+            video: Some(StreamInfo { frame_rate, duration: Some(60 * SECOND) }),
+            ..Default::default()
+        };
+        // 2 frames at 10 fps
+        assert_eq!(Duration::of(&media(Some(SECOND / 10))).unwrap().tolerance, SECOND / 5);
+        // 2 frames at 25 fps are less than a tenth of a second
+        assert_eq!(Duration::of(&media(Some(SECOND / 25))).unwrap().tolerance, SECOND / 10);
+        assert_eq!(Duration::of(&media(None)).unwrap().tolerance, SECOND / 10);
+        assert!(Duration::of(&Media::default()).is_none());
+    }
+}
@@ -0,0 +1,334 @@
+// Media path rewriting (`remap` subcommand): copy a project with the media paths in
+// ActualMediaFilePath, FilePath, RelativePath and the other path elements read by
+// --list-assets rewritten, e.g. after footage moves from E:\Shoot to \\nas\shoot.
+// The original project is never modified.
+
+use clap::Args;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use quick_xml::events::{BytesText, Event};
+use quick_xml::{Reader, Writer};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+use crate::{is_path_name, media_asset_path, open_project_reader};
+
+#[derive(Args, Debug)]
+pub struct RemapArgs {
+    /// Project to rewrite (left untouched)
+    #[arg(value_name = "PROJECT")]
+    pub project: PathBuf,
+
+    /// Prefix rewrite rule, e.g. "E:\Shoot=\\nas\shoot" (repeatable; the longest matching prefix wins)
+    #[arg(long = "map", value_name = "FROM=TO", required = true)]
+    pub rules: Vec<String>,
+
+    /// Where to write the rewritten project (defaults to <name>.remapped.prproj next to it)
+    #[arg(short, long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+
+    /// Print the changes without writing a new project
+    #[arg(long, default_value_t = false)]
+    pub dry_run: bool,
+}
+
+/// Replace the leading `from` of a media path with `to`
+pub struct PrefixRule {
+    from: String,
+    to: String,
+}
+
+impl PrefixRule {
+    /// Parse "FROM=TO"
+    pub fn parse(text: &str) -> Result<PrefixRule, String> {
+        let (from, to) = text
+            .split_once('=')
+            .ok_or_else(|| format!("invalid rule '{}': expected FROM=TO", text))?;
+        let from = from.trim().replace('/', "\\").trim_end_matches('\\').to_string();
+        let to = to.trim().trim_end_matches(['\\', '/']).to_string();
+        if from.is_empty() || to.is_empty() {
+            return Err(format!("invalid rule '{}': FROM and TO must not be empty", text));
+        }
+        Ok(PrefixRule { from, to })
+    }
+
+    /// The rewritten path when `path` (normalized, '\' separators) starts with this
+    /// rule's prefix, compared case-insensitively on whole path components
+    fn apply(&self, path: &str) -> Option<String> {
+        let head = path.get(..self.from.len())?;
+        let rest = &path[self.from.len()..];
+        if !head.eq_ignore_ascii_case(&self.from) || !(rest.is_empty() || rest.starts_with('\\')) {
+            return None;
+        }
+        // Follow the separator style of the new location ("/Volumes/shoot" stays with '/')
+        if self.to.contains('/') && !self.to.contains('\\') {
+            Some(format!("{}{}", self.to, rest.replace('\\', "/")))
+        } else {
+            Some(format!("{}{}", self.to, rest))
+        }
+    }
+}
+
+/// Longest-prefix-first set of rules
+pub fn apply_rules(rules: &[PrefixRule], path: &str) -> Option<String> {
+    rules
+        .iter()
+        .filter(|rule| rule.apply(path).is_some())
+        .max_by_key(|rule| rule.from.len())
+        .and_then(|rule| rule.apply(path))
+}
+
+/// One distinct rewrite and how many path values it changed
+#[derive(Debug)]
+pub struct PathChange {
+    pub old: String,
+    pub new: String,
+    pub count: usize,
+}
+
+/// What a rewrite did (or, without a destination, would do)
+#[derive(Debug, Default)]
+pub struct RewriteReport {
+    /// Sorted by old path
+    pub changes: Vec<PathChange>,
+    /// Distinct media paths that `map` left alone
+    pub unchanged: Vec<String>,
+}
+
+impl RewriteReport {
+    pub fn values_changed(&self) -> usize {
+        self.changes.iter().map(|c| c.count).sum()
+    }
+}
+
+fn is_gzip(path: &Path) -> io::Result<bool> {
+    let mut magic = [0u8; 2];
+    let n = File::open(path)?.read(&mut magic)?;
+    Ok(n == 2 && magic == [0x1F, 0x8B])
+}
+
+fn is_rewritable(name: &[u8]) -> bool {
+    let name = String::from_utf8_lossy(name);
+    is_path_name(&name) || name.eq_ignore_ascii_case("ActualMediaFilePath")
+}
+
+/// Rewrite the media paths of `source` with `map`, which gets each normalized media
+/// path and returns its new location (None leaves it as it is). The result goes to
+/// `dest`, gzip-compressed when the source is; nothing is written when `dest` is None.
+pub fn rewrite_media_paths(
+    source: &Path,
+    dest: Option<&Path>,
+    map: impl Fn(&str) -> Option<String>,
+) -> io::Result<RewriteReport> {
+    let mut bytes = Vec::new();
+    BufReader::new(open_project_reader(source)?).read_to_end(&mut bytes)?;
+
+    let mut changes: BTreeMap<(String, String), usize> = BTreeMap::new();
+    let mut unchanged: BTreeSet<String> = BTreeSet::new();
+    let mut rewrite = |raw: &str| -> Option<String> {
+        let path = media_asset_path(raw)?;
+        match map(&path) {
+            Some(new) if new != path => {
+                *changes.entry((path, new.clone())).or_default() += 1;
+                Some(new)
+            }
+            _ => {
+                unchanged.insert(path);
+                None
+            }
+        }
+    };
+
+    let invalid = |e: quick_xml::Error| io::Error::new(io::ErrorKind::InvalidData, e);
+    let mut reader = Reader::from_reader(bytes.as_slice());
+    let mut writer = Writer::new(Vec::with_capacity(bytes.len()));
+    let mut buf = Vec::new();
+    let mut in_path_element = false;
+
+    loop {
+        let event = reader.read_event_into(&mut buf).map_err(invalid)?;
+        let event = match event {
+            Event::Start(ref e) | Event::Empty(ref e) => {
+                in_path_element = matches!(event, Event::Start(_)) && is_rewritable(e.name().as_ref());
+                let mut attrs: Vec<(Vec<u8>, Vec<u8>)> = Vec::new();
+                let mut changed = false;
+                for attr in e.attributes().with_checks(false).flatten() {
+                    let new = if is_rewritable(attr.key.as_ref()) {
+                        attr.unescape_value().ok().and_then(|val| rewrite(&val))
+                    } else {
+                        None
+                    };
+                    let value = match new {
+                        Some(new) => {
+                            changed = true;
+                            quick_xml::escape::escape(&new).into_owned().into_bytes()
+                        }
+                        None => attr.value.to_vec(),
+                    };
+                    attrs.push((attr.key.as_ref().to_vec(), value));
+                }
+                if changed {
+                    let mut elem = e.to_owned();
+                    elem.clear_attributes();
+                    for (key, value) in &attrs {
+                        elem.push_attribute((key.as_slice(), value.as_slice()));
+                    }
+                    if matches!(event, Event::Start(_)) {
+                        Event::Start(elem)
+                    } else {
+                        Event::Empty(elem)
+                    }
+                } else {
+                    event
+                }
+            }
+            Event::Text(ref t) if in_path_element => {
+                match t.unescape().ok().and_then(|val| rewrite(val.trim())) {
+                    Some(new) => Event::Text(BytesText::new(&new).into_owned()),
+                    None => event,
+                }
+            }
+            Event::End(_) => {
+                in_path_element = false;
+                event
+            }
+            Event::Eof => break,
+            other => other,
+        };
+        writer.write_event(event).map_err(invalid)?;
+        buf.clear();
+    }
+
+    if let Some(dest) = dest {
+        let xml = writer.into_inner();
+        let file = BufWriter::new(File::create(dest)?);
+        if is_gzip(source)? {
+            let mut encoder = GzEncoder::new(file, Compression::default());
+            encoder.write_all(&xml)?;
+            encoder.finish()?.flush()?;
+        } else {
+            let mut file = file;
+            file.write_all(&xml)?;
+            file.flush()?;
+        }
+    }
+
+    Ok(RewriteReport {
+        changes: changes
+            .into_iter()
+            .map(|((old, new), count)| PathChange { old, new, count })
+            .collect(),
+        unchanged: unchanged.into_iter().collect(),
+    })
+}
+
+/// <dir>/<name>.<tag>.prproj, next to the project
+pub fn sibling_output(project: &Path, tag: &str) -> PathBuf {
+    let stem = project.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    let ext = project.extension().map(|e| e.to_string_lossy().to_string()).unwrap_or_else(|| "prproj".to_string());
+    project.with_file_name(format!("{}.{}.{}", stem, tag, ext))
+}
+
+/// Whether two paths name the same file (the output must never replace the original)
+pub fn same_file(a: &Path, b: &Path) -> bool {
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// Print the rewrites in a report, one line per distinct path
+pub fn print_changes(report: &RewriteReport) {
+    for change in &report.changes {
+        let times = if change.count == 1 { String::new() } else { format!("  ({} values)", change.count) };
+        println!("  {}\n    -> {}{}", change.old, change.new, times);
+    }
+}
+
+/// Run the `remap` subcommand; returns the process exit code
+pub fn run(args: &RemapArgs) -> i32 {
+    let rules: Vec<PrefixRule> = match args.rules.iter().map(|r| PrefixRule::parse(r)).collect() {
+        Ok(rules) => rules,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return 1;
+        }
+    };
+    let output = args.output.clone().unwrap_or_else(|| sibling_output(&args.project, "remapped"));
+    if same_file(&args.project, &output) {
+        eprintln!("Error: the output would replace the original project; choose another --output");
+        return 1;
+    }
+
+    let dest = (!args.dry_run).then_some(output.as_path());
+    let report = match rewrite_media_paths(&args.project, dest, |path| apply_rules(&rules, path)) {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("Error: cannot remap {}: {}", args.project.display(), e);
+            return 1;
+        }
+    };
+
+    println!("Remapping media paths in {}", args.project.display());
+    print_changes(&report);
+    let files = |n: usize| format!("{} media {}", n, if n == 1 { "file" } else { "files" });
+    println!(
+        "\n{} path values rewritten ({}); {} matched no rule",
+        report.values_changed(),
+        files(report.changes.len()),
+        files(report.unchanged.len())
+    );
+    if args.dry_run {
+        println!("Dry run: nothing written");
+    } else {
+        println!("Wrote {}", output.display());
+    }
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(texts: &[&str]) -> Vec<PrefixRule> {
+        texts.iter().map(|t| PrefixRule::parse(t).unwrap()).collect()
+    }
+
+    #[test]
+    fn prefix_matches_whole_components_only() {
+        let rules = rules(&["D:\\Shoot=E:\\Archive"]);
+        assert_eq!(apply_rules(&rules, "D:\\Shoot\\a.mov").as_deref(), Some("E:\\Archive\\a.mov"));
+        assert_eq!(apply_rules(&rules, "D:\\Shoot").as_deref(), Some("E:\\Archive"));
+        assert_eq!(apply_rules(&rules, "D:\\Shooting\\a.mov"), None);
+        assert_eq!(apply_rules(&rules, "C:\\D:\\Shoot\\a.mov"), None);
+    }
+
+    #[test]
+    fn prefix_is_case_insensitive() {
+        let rules = rules(&["d:/shoot/=E:\\Archive"]);
+        assert_eq!(apply_rules(&rules, "D:\\SHOOT\\Day 1\\a.mov").as_deref(), Some("E:\\Archive\\Day 1\\a.mov"));
+    }
+
+    #[test]
+    fn longest_prefix_wins() {
+        let rules = rules(&["D:\\Shoot=E:\\Archive", "D:\\Shoot\\Audio=F:\\Sound"]);
+        assert_eq!(apply_rules(&rules, "D:\\Shoot\\Audio\\a.wav").as_deref(), Some("F:\\Sound\\a.wav"));
+        assert_eq!(apply_rules(&rules, "D:\\Shoot\\Video\\a.mov").as_deref(), Some("E:\\Archive\\Video\\a.mov"));
+    }
+
+    #[test]
+    fn separators_follow_the_new_location() {
+        let rules = rules(&["D:\\Shoot=/Volumes/shoot/"]);
+        assert_eq!(apply_rules(&rules, "D:\\Shoot\\Day 1\\a.mov").as_deref(), Some("/Volumes/shoot/Day 1/a.mov"));
+    }
+
+    #[test]
+    fn invalid_rules_are_rejected() {
+        assert!(PrefixRule::parse("D:\\Shoot").is_err());
+        assert!(PrefixRule::parse("=E:\\Archive").is_err());
+        assert!(PrefixRule::parse("D:\\Shoot=").is_err());
+        assert!(PrefixRule::parse("\\=E:\\Archive").is_err());
+    }
+}
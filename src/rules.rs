@@ -0,0 +1,270 @@
+// Include / exclude path rules (--include, --exclude, config `include` / `exclude`,
+// and a .premierehunterignore file at each search root), with gitignore-style
+// syntax and case-insensitive matching against the full path.
+//
+// Self-contained (std and globset only) so the traversal benchmark can include it.
+
+use globset::{Glob, GlobBuilder, GlobSet, GlobSetBuilder};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Read from the top of each search root
+pub const IGNORE_FILE_NAME: &str = ".premierehunterignore";
+
+#[derive(Clone, Debug)]
+struct Rule {
+    /// `!pattern`: re-include what an earlier rule excluded
+    negated: bool,
+    /// `pattern/`: only matches directories
+    dir_only: bool,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct PathRules {
+    /// Files must match one of these when any are given
+    include: Option<GlobSet>,
+    exclude: Option<GlobSet>,
+    /// Parallel to the globs in `exclude`
+    rules: Vec<Rule>,
+    /// Ignore files that were found and loaded
+    pub ignore_files: Vec<PathBuf>,
+}
+
+/// Paths and patterns are compared with '/' separators
+fn normalize(text: &str) -> String {
+    text.replace('\\', "/")
+}
+
+fn glob(pattern: &str) -> Result<Glob, String> {
+    GlobBuilder::new(pattern)
+        .literal_separator(true)
+        .case_insensitive(true)
+        .backslash_escape(false)
+        .build()
+        .map_err(|e| e.to_string())
+}
+
+/// Search root as a glob prefix: '/' separators, no trailing '/', metacharacters escaped
+fn glob_base(root: &Path) -> String {
+    globset::escape(normalize(&root.to_string_lossy()).trim_end_matches('/'))
+}
+
+/// Turn one gitignore-style pattern into full-path globs. Absolute patterns are used
+/// as they are; a pattern without a '/' (or starting with "**/") matches at any depth.
+/// Other patterns, and in an ignore file those with a leading '/', are anchored to
+/// the ignore file's root (`ignore_base`), or to every search root.
+fn anchor(pattern: &str, ignore_base: Option<&str>, roots: &[String]) -> Vec<String> {
+    let has_drive = pattern.as_bytes().get(1) == Some(&b':');
+    if has_drive || (ignore_base.is_none() && pattern.starts_with('/')) {
+        return vec![pattern.to_string()];
+    }
+    let relative = pattern.trim_start_matches('/');
+    let any_depth = !pattern.contains('/') || relative.starts_with("**/");
+    let relative = if any_depth && !relative.starts_with("**/") {
+        format!("**/{}", relative)
+    } else {
+        relative.to_string()
+    };
+    match ignore_base {
+        Some(base) => vec![format!("{}/{}", base, relative)],
+        None if any_depth => vec![relative],
+        None => roots.iter().map(|root| format!("{}/{}", root, relative)).collect(),
+    }
+}
+
+impl PathRules {
+    /// Build the rules from include / exclude globs and the ignore file of each root
+    pub fn new(include: &[String], exclude: &[String], roots: &[PathBuf]) -> Result<PathRules, String> {
+        let mut path_rules = PathRules::default();
+        let bases: Vec<String> = roots.iter().map(|root| glob_base(root)).collect();
+
+        if !include.is_empty() {
+            let mut builder = GlobSetBuilder::new();
+            for pattern in include {
+                for full in anchor(&normalize(pattern), None, &bases) {
+                    builder.add(glob(&full)?);
+                }
+            }
+            path_rules.include = Some(builder.build().map_err(|e| e.to_string())?);
+        }
+
+        // (pattern, rule) in precedence order: the last matching rule wins
+        let mut lines: Vec<(String, Option<String>)> = exclude.iter().map(|p| (p.clone(), None)).collect();
+        for (root, base) in roots.iter().zip(&bases) {
+            let file = root.join(IGNORE_FILE_NAME);
+            let Ok(content) = fs::read_to_string(&file) else {
+                continue;
+            };
+            lines.extend(content.lines().map(|line| (line.to_string(), Some(base.clone()))));
+            path_rules.ignore_files.push(file);
+        }
+
+        let mut builder = GlobSetBuilder::new();
+        for (line, base) in lines {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (negated, line) = match line.strip_prefix('!') {
+                Some(rest) => (true, rest),
+                None => (false, line),
+            };
+            let line = normalize(line);
+            let dir_only = line.ends_with('/');
+            for pattern in anchor(line.trim_end_matches('/'), base.as_deref(), &bases) {
+                // "dir/**" also matches "dir" itself, so the walk skips the whole directory
+                if let Some(dir) = pattern.strip_suffix("/**") {
+                    builder.add(glob(dir)?);
+                    path_rules.rules.push(Rule { negated, dir_only: true });
+                }
+                builder.add(glob(&pattern)?);
+                path_rules.rules.push(Rule { negated, dir_only });
+            }
+        }
+        if !path_rules.rules.is_empty() {
+            path_rules.exclude = Some(builder.build().map_err(|e| e.to_string())?);
+        }
+
+        Ok(path_rules)
+    }
+
+    /// Whether the exclude rules drop this file or directory
+    pub fn is_excluded(&self, path: &Path, is_dir: bool) -> bool {
+        let Some(ref exclude) = self.exclude else {
+            return false;
+        };
+        let candidate = normalize(&path.to_string_lossy());
+        exclude
+            .matches(candidate.as_str())
+            .into_iter()
+            .rev()
+            .map(|i| &self.rules[i])
+            .find(|rule| is_dir || !rule.dir_only)
+            .is_some_and(|rule| !rule.negated)
+    }
+
+    /// Whether a file passes the include globs (always, when there are none)
+    pub fn is_included(&self, path: &Path) -> bool {
+        match self.include {
+            Some(ref include) => include.is_match(normalize(&path.to_string_lossy()).as_str()),
+            None => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(exclude: &[&str], roots: &[&str]) -> PathRules {
+        let exclude: Vec<String> = exclude.iter().map(|p| p.to_string()).collect();
+        let roots: Vec<PathBuf> = roots.iter().map(PathBuf::from).collect();
+        PathRules::new(&[], &exclude, &roots).unwrap()
+    }
+
+    fn dir(rules: &PathRules, path: &str) -> bool {
+        rules.is_excluded(Path::new(path), true)
+    }
+
+    fn file(rules: &PathRules, path: &str) -> bool {
+        rules.is_excluded(Path::new(path), false)
+    }
+
+    #[test]
+    fn later_negation_re_includes() {
+        let rules = rules(&["*.prproj", "!keep*.prproj"], &["/jobs"]);
+        assert!(file(&rules, "/jobs/edit.prproj"));
+        assert!(!file(&rules, "/jobs/client/Keep-Final.prproj"));
+
+        let rules = self::rules(&["!keep*.prproj", "*.prproj"], &["/jobs"]);
+        assert!(file(&rules, "/jobs/keep-final.prproj"));
+
+        let rules = self::rules(&["temp*", "!temp-keep*", "temp-keep-old*"], &["/jobs"]);
+        assert!(file(&rules, "/jobs/temp.prproj"));
+        assert!(!file(&rules, "/jobs/temp-keep.prproj"));
+        assert!(file(&rules, "/jobs/temp-keep-old.prproj"));
+    }
+
+    #[test]
+    fn trailing_slash_only_matches_directories() {
+        let rules = rules(&["Backups/"], &["/jobs"]);
+        assert!(dir(&rules, "/jobs/Backups"));
+        assert!(dir(&rules, "/jobs/client/backups"));
+        assert!(!file(&rules, "/jobs/Backups"));
+        // Files inside are skipped by the walk, which never enters the directory
+        assert!(!file(&rules, "/jobs/Backups/edit.prproj"));
+    }
+
+    #[test]
+    fn double_star_suffix_also_matches_the_directory() {
+        let rules = rules(&["Archive/**"], &["/jobs"]);
+        assert!(dir(&rules, "/jobs/Archive"));
+        assert!(dir(&rules, "/jobs/Archive/2023"));
+        assert!(file(&rules, "/jobs/archive/2023/edit.prproj"));
+        assert!(!file(&rules, "/jobs/Archive"));
+        // A pattern with a '/' is anchored to the root
+        assert!(!dir(&rules, "/jobs/client/Archive"));
+        assert!(!file(&rules, "/jobs/Archived/edit.prproj"));
+    }
+
+    #[test]
+    fn drive_anchored_patterns() {
+        for pattern in ["D:/Archive/20*/**", "D:\\Archive\\20*\\**"] {
+            let rules = rules(&[pattern], &["D:\\", "E:\\"]);
+            assert!(dir(&rules, "D:\\Archive\\2023"));
+            assert!(file(&rules, "D:\\Archive\\2023\\edit.prproj"));
+            assert!(file(&rules, "d:/archive/2019/client/edit.prproj"));
+            assert!(!dir(&rules, "D:\\Archive\\1999"));
+            assert!(!file(&rules, "E:\\Archive\\2023\\edit.prproj"));
+            assert!(!file(&rules, "D:\\Jobs\\Archive\\2023\\edit.prproj"));
+        }
+    }
+
+    #[test]
+    fn ignore_file_patterns_are_anchored_to_its_own_root() {
+        // Glob metacharacters in the root's own name are matched literally
+        let base = std::env::temp_dir().join(format!("premiere-hunter-rules-{}", std::process::id()));
+        let with_file = base.join("jobs [2024]");
+        let without = base.join("other");
+        fs::create_dir_all(&with_file).unwrap();
+        fs::create_dir_all(&without).unwrap();
+        fs::write(
+            with_file.join(IGNORE_FILE_NAME),
+            "# renders and caches\n/Cache\nClient/Drafts/**\n\n*.old.prproj\n!keep.old.prproj\n",
+        )
+        .unwrap();
+
+        let rules = PathRules::new(&[], &["Media/Proxies".to_string()], &[with_file.clone(), without.clone()]).unwrap();
+        assert_eq!(rules.ignore_files, vec![with_file.join(IGNORE_FILE_NAME)]);
+
+        let under = |root: &Path, rel: &str| root.join(rel).to_string_lossy().into_owned();
+        assert!(dir(&rules, &under(&with_file, "Cache")));
+        assert!(!dir(&rules, &under(&with_file, "sub/Cache")));
+        assert!(file(&rules, &under(&with_file, "Client/Drafts/edit.prproj")));
+        assert!(!file(&rules, &under(&with_file, "sub/Client/Drafts/edit.prproj")));
+        assert!(file(&rules, &under(&with_file, "sub/edit.old.prproj")));
+        assert!(!file(&rules, &under(&with_file, "sub/keep.old.prproj")));
+
+        // The other root has no ignore file of its own
+        assert!(!dir(&rules, &under(&without, "Cache")));
+        assert!(!file(&rules, &under(&without, "Client/Drafts/edit.prproj")));
+        assert!(!file(&rules, &under(&without, "edit.old.prproj")));
+
+        // --exclude patterns with a '/' are anchored to every root
+        assert!(dir(&rules, &under(&with_file, "Media/Proxies")));
+        assert!(dir(&rules, &under(&without, "Media/Proxies")));
+        assert!(!dir(&rules, &under(&without, "sub/Media/Proxies")));
+
+        fs::remove_dir_all(&base).unwrap();
+    }
+
+    #[test]
+    fn include_globs() {
+        let include = vec!["Client/**".to_string(), "*final*".to_string()];
+        let rules = PathRules::new(&include, &[], &[PathBuf::from("/jobs")]).unwrap();
+        assert!(rules.is_included(Path::new("/jobs/client/edit.prproj")));
+        assert!(rules.is_included(Path::new("/jobs/other/FINAL.prproj")));
+        assert!(!rules.is_included(Path::new("/jobs/other/edit.prproj")));
+        assert!(PathRules::default().is_included(Path::new("/jobs/other/edit.prproj")));
+    }
+}
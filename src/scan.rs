@@ -0,0 +1,249 @@
+// Streaming byte-level search of a project's text: the input is read in fixed-size
+// chunks, and each chunk is searched together with the tail of the previous one so
+// matches across chunk boundaries are still found. Nothing is decoded or split
+// into lines, and the folded copy used for case-insensitive matching is reused.
+
+use memchr::{memchr, memrchr};
+use std::io::{self, Read};
+
+use crate::query::Query;
+
+/// Bytes read per chunk
+pub const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Fill `buf` from `reader`, stopping short only at end of input
+fn read_chunk(reader: &mut impl Read, buf: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
+}
+
+/// Call `visit` with each window (carried tail plus the next chunk) and its
+/// ASCII-lowercased copy, until end of input or until `visit` returns false.
+/// `carry` gives the number of trailing bytes of a window to search again with
+/// the next chunk.
+pub fn scan_chunks(
+    mut reader: impl Read,
+    carry: impl Fn(&[u8]) -> usize,
+    mut visit: impl FnMut(&[u8], &[u8]) -> bool,
+) -> io::Result<()> {
+    let mut window: Vec<u8> = Vec::with_capacity(2 * CHUNK_SIZE);
+    let mut folded: Vec<u8> = Vec::with_capacity(2 * CHUNK_SIZE);
+    loop {
+        let carried = window.len();
+        window.resize(carried + CHUNK_SIZE, 0);
+        let n = read_chunk(&mut reader, &mut window[carried..])?;
+        if n == 0 {
+            return Ok(());
+        }
+        window.truncate(carried + n);
+        folded.extend(window[carried..].iter().map(u8::to_ascii_lowercase));
+
+        if !visit(&window, &folded) {
+            return Ok(());
+        }
+
+        let keep = carry(&window).min(window.len());
+        let from = window.len() - keep;
+        window.drain(..from);
+        folded.drain(..from);
+    }
+}
+
+/// Whether the query matches anywhere in `reader`
+pub fn stream_contains(reader: impl Read, query: &Query) -> io::Result<bool> {
+    let mut found = vec![false; query.terms.len()];
+    let mut outcome = None;
+    scan_chunks(reader, |window| query.carry(window), |text, folded| {
+        for (i, pattern) in query.patterns.iter().enumerate() {
+            if !found[i] && pattern.find(text, folded).is_some() {
+                found[i] = true;
+            }
+        }
+        // Stop as soon as the outcome can no longer change
+        outcome = query.evaluate(&found, false);
+        outcome.is_none()
+    })?;
+    Ok(outcome.or_else(|| query.evaluate(&found, true)).unwrap_or(false))
+}
+
+/// Like `stream_contains`, returning the text around the first positive term's match.
+/// An empty snippet means the query matched without any positive term.
+pub fn stream_snippet(reader: impl Read, query: &Query, snippet_chars: usize) -> io::Result<Option<String>> {
+    let total_chars = if snippet_chars == 0 { 120 } else { snippet_chars };
+    let half = total_chars / 2;
+
+    let mut found = vec![false; query.terms.len()];
+    let mut first_snippet: Option<String> = None;
+    let mut outcome = None;
+    // Carry extra context so a match near the start of a chunk keeps the text before it
+    scan_chunks(reader, |window| query.carry(window) + half, |text, folded| {
+        for (i, pattern) in query.patterns.iter().enumerate() {
+            if found[i] {
+                continue;
+            }
+            if let Some((start, end)) = pattern.find(text, folded) {
+                found[i] = true;
+                if first_snippet.is_none() && query.is_positive(i) {
+                    first_snippet = Some(snippet_at(text, start, end, half));
+                }
+            }
+        }
+        outcome = query.evaluate(&found, false);
+        outcome.is_none()
+    })?;
+
+    let matched = outcome.or_else(|| query.evaluate(&found, true)).unwrap_or(false);
+    Ok(matched.then(|| first_snippet.unwrap_or_default()))
+}
+
+fn is_continuation(b: u8) -> bool {
+    b & 0xC0 == 0x80
+}
+
+/// Roughly `half` bytes of context on each side of text[start..end), kept within its line
+fn snippet_at(text: &[u8], start: usize, end: usize, half: usize) -> String {
+    let line_start = memrchr(b'\n', &text[..start]).map_or(0, |i| i + 1);
+    let mut line_end = memchr(b'\n', &text[end..]).map_or(text.len(), |i| end + i);
+    if line_end > end && text[line_end - 1] == b'\r' {
+        line_end -= 1;
+    }
+
+    // Widen the start and narrow the end to whole UTF-8 characters
+    let mut from = start.saturating_sub(half).max(line_start);
+    while from > line_start && is_continuation(text[from]) {
+        from -= 1;
+    }
+    let mut to = (end + half).min(line_end);
+    while to > end && to < line_end && is_continuation(text[to]) {
+        to -= 1;
+    }
+
+    let snippet = String::from_utf8_lossy(&text[from..to]).replace('\t', " ");
+    let prefix = if from > line_start { "..." } else { "" };
+    let suffix = if to < line_end { "..." } else { "" };
+    format!("{}{}{}", prefix, snippet, suffix)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(text: &str, use_regex: bool) -> Query {
+        Query::parse(text, use_regex).unwrap()
+    }
+
+    fn contains(text: &[u8], query_text: &str, use_regex: bool) -> bool {
+        stream_contains(text, &query(query_text, use_regex)).unwrap()
+    }
+
+    /// `fill` bytes of padding with `middle` placed so it starts at byte `at`
+    fn padded(at: usize, middle: &[u8], fill: u8) -> Vec<u8> {
+        let mut text = vec![fill; at];
+        text.extend_from_slice(middle);
+        text.extend(std::iter::repeat_n(fill, CHUNK_SIZE));
+        text
+    }
+
+    /// A reader handing out a few bytes at a time, as a decompressor might
+    struct Trickle<'a>(&'a [u8]);
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let n = buf.len().min(self.0.len()).min(1000);
+            buf[..n].copy_from_slice(&self.0[..n]);
+            self.0 = &self.0[n..];
+            Ok(n)
+        }
+    }
+
+    #[test]
+    fn literal_straddling_the_chunk_boundary() {
+        let needle = b"Clair de Lune.wav";
+        for at in CHUNK_SIZE - needle.len()..=CHUNK_SIZE {
+            let text = padded(at, needle, b'\n');
+            assert!(contains(&text, "clair de lune", false), "match at {}", at);
+            assert!(stream_contains(Trickle(&text), &query("LUNE.WAV", false)).unwrap(), "match at {}", at);
+        }
+        assert!(!contains(&padded(CHUNK_SIZE - 4, b"Clair de", b'\n'), "clair de lune", false));
+    }
+
+    #[test]
+    fn regex_straddling_the_chunk_boundary_carries_the_line() {
+        // The anchored line starts well before the boundary and ends after it
+        let line = b"<FilePath>D:\\Shoot\\A001_C002.mov</FilePath>";
+        for at in [CHUNK_SIZE - 30, CHUNK_SIZE - 10, CHUNK_SIZE - 1] {
+            let text = padded(at, line, b'\n');
+            assert!(contains(&text, r"^<FilePath>.*_C\d+\.mov</FilePath>$", true), "line at {}", at);
+            assert!(contains(&text, "\\\\a001_c002", true), "line at {}", at);
+        }
+        // Non-ASCII literals are searched as regexes and carried the same way
+        let text = padded(CHUNK_SIZE - 3, "<Title>Éclair</Title>".as_bytes(), b'\n');
+        assert!(contains(&text, "ÉCLAIR", false));
+    }
+
+    #[test]
+    fn boolean_terms_found_in_different_chunks() {
+        let mut text = padded(10, b"first", b' ');
+        text.extend(padded(CHUNK_SIZE, b"second", b' '));
+        assert!(contains(&text, "first AND second", false));
+        assert!(!contains(&text, "first AND NOT second", false));
+        assert!(contains(&text, "NOT third", false));
+    }
+
+    #[test]
+    fn invalid_utf8_is_searched_as_bytes() {
+        let text = b"<Name>\xff\xfeClip\xc3</Name>\n<Path>\x80D:\\plane.mov</Path>";
+        assert!(contains(text, "clip", false));
+        assert!(contains(text, "d:\\plane", false));
+        assert!(contains(text, r"\\plane\.mov</Path>$", true));
+        assert!(!contains(text, "train", false));
+        let snippet = stream_snippet(&text[..], &query("clip", false), 0).unwrap().unwrap();
+        assert_eq!(snippet, "<Name>\u{fffd}\u{fffd}Clip\u{fffd}</Name>");
+    }
+
+    #[test]
+    fn single_huge_line_without_newlines() {
+        let text = padded(3 * CHUNK_SIZE - 2, b"needle", b'a');
+        assert!(contains(&text, "needle", false));
+        assert!(contains(&text, "a+needle", true));
+        assert!(!contains(&text, "^needle", true));
+        let snippet = stream_snippet(&text[..], &query("needle", false), 20).unwrap().unwrap();
+        assert_eq!(snippet, "...aaaaaaaaaaneedleaaaaaaaaaa...");
+    }
+
+    #[test]
+    fn snippet_keeps_context_from_the_previous_chunk() {
+        let text = padded(CHUNK_SIZE + 2, b"<Path>clip.mov</Path>", b'x');
+        let snippet = stream_snippet(&text[..], &query("clip.mov", false), 10).unwrap().unwrap();
+        assert_eq!(snippet, "...Path>clip.mov</Pat...");
+    }
+
+    #[test]
+    fn snippet_cuts_do_not_split_characters() {
+        let text = "ééééé needle ééééé\nnext".as_bytes();
+        for half in 1..12 {
+            let start = text.windows(6).position(|w| w == b"needle").unwrap();
+            let snippet = snippet_at(text, start, start + 6, half);
+            assert!(!snippet.contains('\u{fffd}'), "half {}: {:?}", half, snippet);
+            assert!(snippet.contains("needle"));
+        }
+        // The start widens to the whole character and the end narrows to before it
+        let start = 11;
+        assert_eq!(snippet_at(text, start, start + 6, 4), "...éé needle é...");
+        assert_eq!(snippet_at(text, start, start + 6, 20), "ééééé needle ééééé");
+    }
+
+    #[test]
+    fn snippet_stays_within_the_line() {
+        let text = b"before\r\n\tclip here\r\nafter";
+        assert_eq!(snippet_at(text, 9, 13, 60), " clip here");
+    }
+}
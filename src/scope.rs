@@ -0,0 +1,190 @@
+// XML-aware scoped search: match only inside the text of selected element
+// kinds (clip names, markers, titles, media paths) instead of the raw file.
+
+use clap::ValueEnum;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{self, BufReader};
+use std::path::Path;
+
+use crate::query::Query;
+use crate::{is_path_name, open_project_reader};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ValueEnum, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ScopeKind {
+    /// <Name> of project items, bins, sequences, tracks and clips
+    Name,
+    /// <ClipName> of clip logging info
+    ClipName,
+    /// Marker names and comments
+    Marker,
+    /// <Title> of media
+    Title,
+    /// Media file paths (FilePath, ActualMediaFilePath, RelativePath, ...)
+    Path,
+}
+
+impl ScopeKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScopeKind::Name => "name",
+            ScopeKind::ClipName => "clip-name",
+            ScopeKind::Marker => "marker",
+            ScopeKind::Title => "title",
+            ScopeKind::Path => "path",
+        }
+    }
+
+    pub fn for_element(name: &str) -> Option<ScopeKind> {
+        match name {
+            "Name" => Some(ScopeKind::Name),
+            "ClipName" => Some(ScopeKind::ClipName),
+            "DVAMarker" => Some(ScopeKind::Marker),
+            "Title" => Some(ScopeKind::Title),
+            _ if is_path_name(name) || name.eq_ignore_ascii_case("actualmediafilepath") => {
+                Some(ScopeKind::Path)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// First matching element text for one scope kind
+pub struct ScopeHit {
+    pub kind: ScopeKind,
+    pub text: String,
+}
+
+// Markers store their name and comment inside a JSON payload:
+// <DVAMarker>{"DVAMarker":{"mName":"...","mComment":"...","mStartTime":{...}}}</DVAMarker>
+fn marker_texts(raw: &str) -> Vec<String> {
+    let value: serde_json::Value = match serde_json::from_str(raw) {
+        Ok(v) => v,
+        Err(_) => return Vec::new(),
+    };
+    let marker = &value["DVAMarker"];
+    ["mName", "mComment"]
+        .iter()
+        .filter_map(|key| marker[*key].as_str())
+        .filter(|s| !s.is_empty())
+        .map(|s| s.to_string())
+        .collect()
+}
+
+struct ScopeState<'a> {
+    query: &'a Query,
+    scopes: &'a [ScopeKind],
+    found: Vec<bool>,
+    hits: Vec<ScopeHit>,
+}
+
+impl ScopeState<'_> {
+    fn check(&mut self, kind: ScopeKind, text: &str) {
+        for (i, term) in self.query.terms.iter().enumerate() {
+            if !term.is_match(text) {
+                continue;
+            }
+            self.found[i] = true;
+            if self.query.is_positive(i) && !self.hits.iter().any(|h| h.kind == kind) {
+                self.hits.push(ScopeHit { kind, text: text.to_string() });
+            }
+        }
+    }
+
+    fn check_path_attributes(&mut self, e: &BytesStart) {
+        if !self.scopes.contains(&ScopeKind::Path) {
+            return;
+        }
+        for attr in e.attributes().with_checks(false).flatten() {
+            let key = String::from_utf8_lossy(attr.key.as_ref());
+            if is_path_name(&key) {
+                if let Ok(val) = attr.unescape_value() {
+                    self.check(ScopeKind::Path, &val);
+                }
+            }
+        }
+    }
+
+    fn all_kinds_hit(&self) -> bool {
+        self.scopes.iter().all(|k| self.hits.iter().any(|h| h.kind == *k))
+    }
+}
+
+/// Evaluate `query` against the text of the selected element kinds only.
+/// Returns the first matching text per kind when the query matches.
+pub fn file_scoped_matches(
+    path: &Path,
+    query: &Query,
+    scopes: &[ScopeKind],
+    max_size_bytes: Option<usize>,
+) -> Result<Option<Vec<ScopeHit>>, io::Error> {
+    // Check file size if limit is set (on-disk size)
+    if let Some(max_bytes) = max_size_bytes {
+        let metadata = fs::metadata(path)?;
+        if metadata.len() > max_bytes as u64 {
+            return Ok(None);
+        }
+    }
+
+    let mut reader = Reader::from_reader(BufReader::new(open_project_reader(path)?));
+    reader.trim_text(true);
+    let mut buf = Vec::new();
+
+    let mut state = ScopeState {
+        query,
+        scopes,
+        found: vec![false; query.terms.len()],
+        hits: Vec::new(),
+    };
+    // Scope kind of each open element, innermost last
+    let mut stack: Vec<Option<ScopeKind>> = Vec::new();
+
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(e)) => {
+                let name = String::from_utf8_lossy(e.name().as_ref()).to_string();
+                stack.push(ScopeKind::for_element(&name).filter(|k| scopes.contains(k)));
+                state.check_path_attributes(&e);
+            }
+            Ok(Event::Empty(e)) => {
+                state.check_path_attributes(&e);
+            }
+            Ok(Event::End(_)) => {
+                stack.pop();
+            }
+            Ok(Event::Text(t)) => {
+                if let Some(Some(kind)) = stack.last().copied() {
+                    if let Ok(val) = t.unescape() {
+                        if kind == ScopeKind::Marker {
+                            for text in marker_texts(&val) {
+                                state.check(kind, &text);
+                            }
+                        } else {
+                            state.check(kind, val.trim());
+                        }
+                    }
+                }
+            }
+            Ok(Event::Eof) => break,
+            Err(e) => return Err(io::Error::new(io::ErrorKind::InvalidData, e)),
+            _ => {}
+        }
+        buf.clear();
+
+        match query.evaluate(&state.found, false) {
+            Some(false) => return Ok(None),
+            // Keep reading only while some selected kind has not reported a hit yet
+            Some(true) if state.all_kinds_hit() => return Ok(Some(state.hits)),
+            _ => {}
+        }
+    }
+
+    if query.evaluate(&state.found, true).unwrap_or(false) {
+        Ok(Some(state.hits))
+    } else {
+        Ok(None)
+    }
+}
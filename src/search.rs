@@ -0,0 +1,393 @@
+// Per-project work: run the selected mode on one file and turn the outcome into
+// an output record. Shared by the initial scan and --watch.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+
+use crate::errors::{ErrorCategory, FileTooLarge};
+use crate::index::Index;
+use crate::media::check_project_media;
+use crate::occurrences::file_all_matches;
+use crate::output::{MediaCounts, MediaRecord, OccurrenceRecord, Record, ScopeRecord, SequenceRecord, Summary, TermHits};
+use crate::project::{frames_per_second, ticks_to_timecode, Project};
+use crate::query::Query;
+use crate::scope::{file_scoped_matches, ScopeKind};
+use crate::terms::{file_terms, TermSet};
+use crate::uses::{file_uses, MediaTarget};
+use crate::{extract_assets_from_prproj, file_contains_case_insensitive, file_snippet_case_insensitive};
+
+pub enum Mode {
+    ListAssets,
+    /// --uses: the file as given, and its normalized form
+    Uses(String, MediaTarget),
+    CheckMedia,
+    ListSequences,
+    /// --term / --terms-file / search_terms: report which of many terms each project contains
+    Terms(TermSet),
+    AllMatches,
+    Scoped,
+    Snippets,
+    Contains,
+}
+
+pub struct Search {
+    pub mode: Mode,
+    /// Search text; an optional filter in the listing modes
+    pub query: Option<Query>,
+    /// Set for --scope (also narrows --all-matches)
+    pub scopes: Option<Vec<ScopeKind>>,
+    pub index: Option<Index>,
+    pub max_size_bytes: Option<usize>,
+    pub snippet_chars: usize,
+}
+
+impl Search {
+    /// Search one project. Returns None when it has nothing to report,
+    /// and a FileTooLarge error when it is over the size limit.
+    pub fn run(&self, path: &Path) -> Result<Option<Record>, io::Error> {
+        let display = path.display().to_string();
+        let max_size_bytes = self.max_size_bytes;
+        if let Some(max_bytes) = max_size_bytes {
+            let size = fs::metadata(path)?.len();
+            if size > max_bytes as u64 {
+                return Err(io::Error::other(FileTooLarge { size, limit: max_bytes as u64 }));
+            }
+        }
+        let query = self.query.as_ref();
+
+        let record = match self.mode {
+            Mode::ListAssets => {
+                let mut assets = match self.index {
+                    Some(ref index) => index
+                        .entry(path, max_size_bytes)?
+                        .map(|e| e.assets)
+                        .unwrap_or_default(),
+                    None => extract_assets_from_prproj(path, max_size_bytes)?,
+                };
+                // Optional filter by substring or regex (case-insensitive)
+                if let Some(filt) = query {
+                    assets.retain(|a| filt.matches_text(a));
+                }
+                (!assets.is_empty()).then_some(Record::Assets { path: display, assets })
+            }
+            Mode::Uses(_, ref target) => file_uses(path, target, max_size_bytes)?.map(|usage| Record::Uses {
+                path: display,
+                assets: usage.assets,
+                sequences: usage.sequences,
+                bins: usage.bins,
+            }),
+            Mode::CheckMedia => {
+                let mut checks = check_project_media(path, max_size_bytes)?;
+                if let Some(filt) = query {
+                    checks.retain(|c| filt.matches_text(&c.asset));
+                }
+                let mut counts = MediaCounts::default();
+                for c in &checks {
+                    counts.add(c.status);
+                }
+                let media: Vec<MediaRecord> = checks
+                    .into_iter()
+                    .map(|c| MediaRecord { asset: c.asset, status: c.status, resolved: c.resolved })
+                    .collect();
+                (!media.is_empty()).then_some(Record::Media { path: display, counts, media })
+            }
+            Mode::ListSequences => {
+                let project = Project::load(path, max_size_bytes)?;
+                let sequences: Vec<SequenceRecord> = project
+                    .sequences
+                    .iter()
+                    .filter(|seq| query.is_none_or(|filt| filt.matches_text(&seq.name)))
+                    .map(|seq| SequenceRecord {
+                        name: seq.name.clone(),
+                        frame_rate: seq.frame_rate.map(frames_per_second),
+                        width: seq.frame_size.map(|(w, _)| w),
+                        height: seq.frame_size.map(|(_, h)| h),
+                        video_tracks: seq.video_tracks.len(),
+                        audio_tracks: seq.audio_tracks.len(),
+                        duration_ticks: seq.duration(),
+                        duration: seq.frame_rate.map(|r| ticks_to_timecode(seq.duration(), r)),
+                    })
+                    .collect();
+                (!sequences.is_empty()).then_some(Record::Sequences { path: display, sequences })
+            }
+            Mode::Terms(ref terms) => {
+                let found = file_terms(path, terms, max_size_bytes)?;
+                (!found.is_empty()).then(|| Record::Terms {
+                    path: display,
+                    terms: found.into_iter().map(|i| terms.labels[i].clone()).collect(),
+                })
+            }
+            Mode::AllMatches => {
+                let q = query.expect("search text");
+                let scopes = self.scopes.as_deref();
+                file_all_matches(path, q, scopes, max_size_bytes, self.snippet_chars)?.map(|occurrences| {
+                    let occurrences = occurrences
+                        .into_iter()
+                        .map(|occ| OccurrenceRecord {
+                            line: occ.line,
+                            element_path: occ.element_path,
+                            term: q.is_boolean().then(|| q.labels[occ.term].clone()),
+                            snippet: occ.snippet,
+                        })
+                        .collect();
+                    Record::Match { path: display, snippet: None, scopes: None, occurrences: Some(occurrences) }
+                })
+            }
+            Mode::Scoped => {
+                let q = query.expect("search text");
+                let kinds = self.scopes.as_deref().unwrap_or_default();
+                file_scoped_matches(path, q, kinds, max_size_bytes)?.map(|hits| {
+                    let max_chars = if self.snippet_chars == 0 { 120 } else { self.snippet_chars };
+                    let scopes = hits
+                        .iter()
+                        .map(|hit| {
+                            let mut text: String = hit.text.chars().take(max_chars).collect();
+                            if text.len() < hit.text.len() {
+                                text.push_str("...");
+                            }
+                            ScopeRecord { kind: hit.kind.as_str().to_string(), text }
+                        })
+                        .collect();
+                    Record::Match { path: display, snippet: None, scopes: Some(scopes), occurrences: None }
+                })
+            }
+            Mode::Snippets => {
+                let q = query.expect("search text");
+                file_snippet_case_insensitive(path, q, max_size_bytes, self.snippet_chars)?.map(|snippet| {
+                    Record::Match { path: display, snippet: Some(snippet), scopes: None, occurrences: None }
+                })
+            }
+            Mode::Contains => {
+                let q = query.expect("search text");
+                let matched = match self.index {
+                    // The index only holds lines, so queries that may match across
+                    // lines (regexes, multi-line terms) go straight to the project
+                    Some(ref index) if q.matches_within_lines() => {
+                        match index.entry(path, max_size_bytes)?.map(|e| e.matches(q)) {
+                            Some(Some(matched)) => matched,
+                            // A project that isn't valid UTF-8 has no indexed lines
+                            Some(None) => file_contains_case_insensitive(path, q, max_size_bytes)?,
+                            None => false,
+                        }
+                    }
+                    _ => file_contains_case_insensitive(path, q, max_size_bytes)?,
+                };
+                matched.then_some(Record::Match { path: display, snippet: None, scopes: None, occurrences: None })
+            }
+        };
+        Ok(record)
+    }
+}
+
+/// Running totals for the final summary, fed from emitted records
+#[derive(Default)]
+pub struct Tally {
+    files_matched: AtomicUsize,
+    total_assets: AtomicUsize,
+    total_sequences: AtomicUsize,
+    total_occurrences: AtomicUsize,
+    media: Mutex<MediaCounts>,
+    /// Projects matched per term
+    term_projects: Mutex<HashMap<String, usize>>,
+    autosave_copies: AtomicUsize,
+    errors: Mutex<BTreeMap<ErrorCategory, usize>>,
+}
+
+impl Tally {
+    pub fn add(&self, record: &Record) {
+        match record {
+            Record::Match { occurrences, .. } => {
+                if let Some(occurrences) = occurrences {
+                    self.total_occurrences.fetch_add(occurrences.len(), Ordering::Relaxed);
+                }
+            }
+            Record::Assets { assets, .. } => {
+                self.total_assets.fetch_add(assets.len(), Ordering::Relaxed);
+            }
+            Record::Sequences { sequences, .. } => {
+                self.total_sequences.fetch_add(sequences.len(), Ordering::Relaxed);
+            }
+            Record::Uses { sequences, .. } => {
+                self.total_sequences.fetch_add(sequences.len(), Ordering::Relaxed);
+            }
+            Record::Media { counts, .. } => self.media.lock().unwrap().merge(counts),
+            Record::Terms { terms, .. } => {
+                let mut term_projects = self.term_projects.lock().unwrap();
+                for term in terms {
+                    *term_projects.entry(term.clone()).or_default() += 1;
+                }
+            }
+            Record::AutoSave { matched, record, .. } => {
+                self.autosave_copies.fetch_add(*matched, Ordering::Relaxed);
+                // Counted as one project, with the newest copy's totals
+                return self.add(record);
+            }
+            Record::Error { category, .. } => {
+                *self.errors.lock().unwrap().entry(*category).or_default() += 1;
+                return;
+            }
+            Record::Unmatched { .. } => return,
+        }
+        self.files_matched.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn autosave_copies(&self) -> usize {
+        self.autosave_copies.load(Ordering::Relaxed)
+    }
+
+    pub fn media_missing(&self) -> usize {
+        self.media.lock().unwrap().missing
+    }
+
+    /// Summary with the mode-specific totals filled in; the caller sets the file counts
+    pub fn summary(&self, mode: &Mode) -> Summary {
+        Summary {
+            files_matched: self.files_matched.load(Ordering::Relaxed),
+            total_assets: matches!(mode, Mode::ListAssets).then(|| self.total_assets.load(Ordering::Relaxed)),
+            uses: match mode {
+                Mode::Uses(file, _) => Some(file.clone()),
+                _ => None,
+            },
+            total_sequences: matches!(mode, Mode::ListSequences | Mode::Uses(..))
+                .then(|| self.total_sequences.load(Ordering::Relaxed)),
+            media: matches!(mode, Mode::CheckMedia).then(|| *self.media.lock().unwrap()),
+            term_hits: match mode {
+                Mode::Terms(terms) => {
+                    let term_projects = self.term_projects.lock().unwrap();
+                    let hits = terms
+                        .labels
+                        .iter()
+                        .map(|term| TermHits {
+                            term: term.clone(),
+                            projects: term_projects.get(term).copied().unwrap_or(0),
+                        })
+                        .collect();
+                    Some(hits)
+                }
+                _ => None,
+            },
+            total_occurrences: matches!(mode, Mode::AllMatches)
+                .then(|| self.total_occurrences.load(Ordering::Relaxed)),
+            errors_by_category: Some(self.errors.lock().unwrap().clone()).filter(|errors| !errors.is_empty()),
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::categorize;
+    use serde_json::json;
+    use std::path::PathBuf;
+
+    const PROJECT: &str = "<Project>\n\
+\t<Media>\n\t\t<FilePath>D:\\Shoot\\Clair de Lune.wav</FilePath>\n\t</Media>\n\
+\t<Media>\n\t\t<FilePath>D:\\Shoot\\Plane.mov</FilePath>\n\t</Media>\n\
+\t<Sequence>\n\t\t<Name>Final mix</Name>\n\t</Sequence>\n\
+</Project>\n";
+
+    /// A fresh directory holding PROJECT as edit.prproj
+    fn project(name: &str) -> (PathBuf, PathBuf) {
+        let dir = std::env::temp_dir().join(format!("premiere-hunter-search-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("edit.prproj");
+        fs::write(&path, PROJECT).unwrap();
+        (dir, path)
+    }
+
+    fn search(mode: Mode, text: Option<&str>, use_regex: bool) -> Search {
+        Search {
+            mode,
+            query: text.map(|t| Query::parse(t, use_regex).unwrap()),
+            scopes: None,
+            index: None,
+            max_size_bytes: None,
+            snippet_chars: 0,
+        }
+    }
+
+    fn run(search: &Search, path: &Path) -> serde_json::Value {
+        serde_json::to_value(search.run(path).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn contains_and_snippets() {
+        let (dir, path) = project("contains");
+        let display = path.display().to_string();
+        let found = run(&search(Mode::Contains, Some("clair de lune"), false), &path);
+        assert_eq!(found, json!({ "type": "match", "path": display }));
+        assert!(run(&search(Mode::Contains, Some("lune AND NOT final"), false), &path).is_null());
+
+        let found = run(&search(Mode::Snippets, Some("final"), false), &path);
+        assert_eq!(found["snippet"], "  <Name>Final mix</Name>");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn listing_filters_by_the_query() {
+        let (dir, path) = project("listing");
+        let all = run(&search(Mode::ListAssets, None, false), &path);
+        assert_eq!(all["assets"], json!(["D:\\Shoot\\Clair de Lune.wav", "D:\\Shoot\\Plane.mov"]));
+        let filtered = run(&search(Mode::ListAssets, Some("plane"), false), &path);
+        assert_eq!(filtered["assets"], json!(["D:\\Shoot\\Plane.mov"]));
+        assert!(run(&search(Mode::ListAssets, Some("train"), false), &path).is_null());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn index_is_used_only_for_queries_within_lines() {
+        let (dir, path) = project("index");
+        let mut indexed = search(Mode::Contains, Some("lune"), false);
+        indexed.index = Some(Index::open(Some(dir.join("index"))).unwrap());
+        let display = path.display().to_string();
+        assert_eq!(run(&indexed, &path), json!({ "type": "match", "path": display }));
+        assert_eq!(run(&indexed, &path), json!({ "type": "match", "path": display }));
+        assert_eq!(indexed.index.as_ref().unwrap().stats(), (1, 1));
+
+        // A regex may span lines, so the project is read directly
+        indexed.query = Some(Query::parse(r"Lune\.wav</FilePath>\s+</Media>", true).unwrap());
+        assert_eq!(run(&indexed, &path), json!({ "type": "match", "path": display }));
+        assert_eq!(indexed.index.as_ref().unwrap().stats(), (1, 1));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn oversized_projects_are_not_read() {
+        let (dir, path) = project("oversized");
+        let mut limited = search(Mode::Contains, Some("lune"), false);
+        limited.max_size_bytes = Some(PROJECT.len() - 1);
+        let error = limited.run(&path).unwrap_err();
+        assert_eq!(categorize(&error), ErrorCategory::Oversized);
+        limited.max_size_bytes = Some(PROJECT.len());
+        assert!(limited.run(&path).unwrap().is_some());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn tally_counts_matched_projects_and_totals() {
+        let tally = Tally::default();
+        let assets = |n: usize| Record::Assets { path: "a.prproj".to_string(), assets: vec!["x.mov".to_string(); n] };
+        tally.add(&assets(2));
+        tally.add(&assets(3));
+        tally.add(&Record::Unmatched { path: "b.prproj".to_string(), removed: true });
+        tally.add(&Record::Error {
+            path: "c.prproj".to_string(),
+            category: ErrorCategory::MalformedXml,
+            error: "bad".to_string(),
+        });
+
+        let summary = tally.summary(&Mode::ListAssets);
+        assert_eq!(summary.files_matched, 2);
+        assert_eq!(summary.total_assets, Some(5));
+        assert_eq!(summary.total_sequences, None);
+        assert_eq!(summary.errors_by_category, Some(BTreeMap::from([(ErrorCategory::MalformedXml, 1)])));
+        // Totals of other modes are left out
+        assert_eq!(tally.summary(&Mode::Contains).total_assets, None);
+    }
+}
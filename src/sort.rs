@@ -0,0 +1,94 @@
+// Sorted output (--sort): results are held back during the scan and emitted in a
+// fixed order once it ends, so repeated runs over the same files give identical
+// reports. Without it, results stream out in whatever order the workers finish.
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::fs;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use crate::output::{record_path, Record};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortKey {
+    /// Project path, A to Z
+    Path,
+    /// Most recently modified first
+    Modified,
+    /// Largest file first
+    Size,
+    /// Most matches (occurrences, assets, sequences or terms) first
+    Matches,
+}
+
+struct Held {
+    path: String,
+    modified: Option<SystemTime>,
+    size: Option<u64>,
+    matches: usize,
+    record: Record,
+}
+
+pub struct SortedResults {
+    key: SortKey,
+    held: Mutex<Vec<Held>>,
+}
+
+/// Number of things a record reports; 1 for a plain match
+fn match_count(record: &Record) -> usize {
+    match record {
+        Record::Match { occurrences: Some(occurrences), .. } => occurrences.len(),
+        Record::Match { scopes: Some(scopes), .. } => scopes.len(),
+        Record::Match { .. } => 1,
+        Record::Assets { assets, .. } => assets.len(),
+        Record::Sequences { sequences, .. } => sequences.len(),
+        Record::Media { media, .. } => media.len(),
+        Record::Uses { assets, sequences, .. } => assets.len() + sequences.len(),
+        Record::Terms { terms, .. } => terms.len(),
+        Record::AutoSave { record, .. } => match_count(record),
+        Record::Unmatched { .. } | Record::Error { .. } => 0,
+    }
+}
+
+impl SortedResults {
+    pub fn new(key: SortKey) -> SortedResults {
+        SortedResults { key, held: Mutex::new(Vec::new()) }
+    }
+
+    /// Hold a result until `finish`. Safe to call from parallel workers.
+    pub fn hold(&self, record: Record) {
+        let path = record_path(&record).to_string();
+        let metadata = match self.key {
+            SortKey::Modified | SortKey::Size => fs::metadata(&path).ok(),
+            SortKey::Path | SortKey::Matches => None,
+        };
+        let held = Held {
+            modified: metadata.as_ref().and_then(|m| m.modified().ok()),
+            size: metadata.as_ref().map(|m| m.len()),
+            matches: match_count(&record),
+            path,
+            record,
+        };
+        self.held.lock().unwrap().push(held);
+    }
+
+    /// Every held result in order; ties (and files without metadata, which go last)
+    /// are ordered by path
+    pub fn finish(self) -> Vec<Record> {
+        let mut held = self.held.into_inner().unwrap();
+        match self.key {
+            SortKey::Path => held.sort_by(|a, b| a.path.cmp(&b.path)),
+            SortKey::Modified => held.sort_by(|a, b| {
+                (a.modified.is_none(), Reverse(a.modified), &a.path).cmp(&(b.modified.is_none(), Reverse(b.modified), &b.path))
+            }),
+            SortKey::Size => held.sort_by(|a, b| {
+                (a.size.is_none(), Reverse(a.size), &a.path).cmp(&(b.size.is_none(), Reverse(b.size), &b.path))
+            }),
+            SortKey::Matches => held.sort_by(|a, b| (Reverse(a.matches), &a.path).cmp(&(Reverse(b.matches), &b.path))),
+        }
+        held.into_iter().map(|h| h.record).collect()
+    }
+}
@@ -0,0 +1,178 @@
+// Multi-term search (--term, --terms-file, search_terms): check a list of terms,
+// such as hundreds of track titles, against each project in a single pass with
+// an Aho-Corasick automaton, and report which of them each project contains.
+// Terms with non-ASCII letters are matched with a case-insensitive regex set
+// instead, folding case the same way as a single search term.
+
+use aho_corasick::{AhoCorasick, AhoCorasickBuilder, MatchKind};
+use regex::bytes::{RegexSet, RegexSetBuilder};
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::open_project_reader;
+use crate::scan::scan_chunks;
+
+pub struct TermSet {
+    /// Terms as given, deduplicated case-insensitively
+    pub labels: Vec<String>,
+    /// ASCII terms, which only need ASCII case folding
+    automaton: AhoCorasick,
+    /// Term index of each automaton pattern
+    pattern_term: Vec<usize>,
+    /// Terms with other letters, with Unicode case folding
+    unicode: RegexSet,
+    /// Term index of each pattern in `unicode`
+    unicode_term: Vec<usize>,
+    /// Longest possible match in bytes
+    max_len: usize,
+}
+
+impl TermSet {
+    pub fn new(terms: &[String]) -> Result<TermSet, String> {
+        let mut seen: HashSet<String> = HashSet::new();
+        let mut labels: Vec<String> = Vec::new();
+        for term in terms {
+            let term = term.trim();
+            if !term.is_empty() && seen.insert(term.to_lowercase()) {
+                labels.push(term.to_string());
+            }
+        }
+        if labels.is_empty() {
+            return Err("no search terms given".to_string());
+        }
+
+        // The automaton folds ASCII case only, and listing a term's lower- and uppercase
+        // forms would still miss mixed case ("éLAN" for "Élan"), so terms with other
+        // letters go to a regex set with the same case folding as a single search term
+
+        let (ascii, unicode): (Vec<usize>, Vec<usize>) = (0..labels.len()).partition(|&i| labels[i].is_ascii());
+        let automaton = AhoCorasickBuilder::new()
+            .ascii_case_insensitive(true)
+            .match_kind(MatchKind::Standard)
+            .build(ascii.iter().map(|&i| &labels[i]))
+            .map_err(|e| e.to_string())?;
+        let unicode_set = RegexSetBuilder::new(unicode.iter().map(|&i| regex::escape(&labels[i])))
+            .case_insensitive(true)
+            .build()
+            .map_err(|e| e.to_string())?;
+
+        // A folded character takes at most 4 bytes
+        let max_len = labels
+            .iter()
+            .map(|label| if label.is_ascii() { label.len() } else { label.chars().count() * 4 })
+            .max()
+            .unwrap_or(0);
+
+        Ok(TermSet { labels, automaton, pattern_term: ascii, unicode: unicode_set, unicode_term: unicode, max_len })
+    }
+}
+
+/// One term per line; blank lines and lines starting with '#' are skipped
+pub fn load_terms_file(path: &Path) -> Result<Vec<String>, io::Error> {
+    let content = fs::read_to_string(path)?;
+    Ok(content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect())
+}
+
+/// Indexes (into `terms.labels`) of the terms found in a project, in term order
+pub fn file_terms(path: &Path, terms: &TermSet, max_size_bytes: Option<usize>) -> Result<Vec<usize>, io::Error> {
+    // Check file size if limit is set (on-disk size)
+    if let Some(max_bytes) = max_size_bytes {
+        if fs::metadata(path)?.len() > max_bytes as u64 {
+            return Ok(Vec::new());
+        }
+    }
+
+    let mut found = vec![false; terms.labels.len()];
+    let mut remaining = found.len();
+    let carry = terms.max_len.saturating_sub(1);
+    scan_chunks(open_project_reader(path)?, |_| carry, |text, _| {
+        // Overlapping search, so a term inside another ("Lune" in "Clair de Lune") is seen too
+        let ascii = terms.automaton.find_overlapping_iter(text).map(|m| terms.pattern_term[m.pattern().as_usize()]);
+        let unicode = terms.unicode.matches(text).into_iter().map(|i| terms.unicode_term[i]);
+        for term in ascii.chain(unicode) {
+            if !found[term] {
+                found[term] = true;
+                remaining -= 1;
+            }
+        }
+        // Every term found: nothing left to learn from the rest of the file
+        remaining > 0
+    })?;
+
+    Ok((0..found.len()).filter(|&i| found[i]).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query::Query;
+    use crate::scan::{stream_contains, CHUNK_SIZE};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn term_set(terms: &[&str]) -> TermSet {
+        TermSet::new(&terms.iter().map(|t| t.to_string()).collect::<Vec<_>>()).unwrap()
+    }
+
+    /// Labels of the terms found in `content`
+    fn found(terms: &[&str], content: &[u8]) -> Vec<String> {
+        let dir = std::env::temp_dir().join(format!("premiere-hunter-terms-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        // Tests run in parallel, so each call gets its own file
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+        let path = dir.join(format!("{}.prproj", NEXT.fetch_add(1, Ordering::Relaxed)));
+        fs::write(&path, content).unwrap();
+        let set = term_set(terms);
+        let found = file_terms(&path, &set, None).unwrap();
+        fs::remove_file(&path).unwrap();
+        found.into_iter().map(|i| set.labels[i].clone()).collect()
+    }
+
+    #[test]
+    fn terms_are_deduplicated_case_insensitively() {
+        assert_eq!(term_set(&["Lune", " lune ", "LUNE", "Élan", "éLAN", ""]).labels, ["Lune", "Élan"]);
+        assert!(TermSet::new(&[" ".to_string()]).is_err());
+    }
+
+    #[test]
+    fn overlapping_hits_are_all_found() {
+        let terms = ["Clair de Lune", "Lune", "de L", "air", "Clair", "Sunrise"];
+        assert_eq!(found(&terms, b"<Name>CLAIR DE LUNE</Name>"), ["Clair de Lune", "Lune", "de L", "air", "Clair"]);
+        let terms = ["Café", "fé", "Caf", "Café Lune", "é L"];
+        assert_eq!(found(&terms, "<Name>café lune</Name>".as_bytes()), terms);
+    }
+
+    #[test]
+    fn mixed_case_of_non_ascii_terms() {
+        let text = "<Name>éLAN vital</Name><Title>ΣΟΦΊΑ</Title>".as_bytes();
+        assert_eq!(found(&["Élan", "élan vital", "σοφία", "Ελάν"], text), ["Élan", "élan vital", "σοφία"]);
+    }
+
+    #[test]
+    fn terms_fold_case_like_a_single_search_term() {
+        let texts = ["Élan", "éLAN", "ÉLAN", "Elan", "straße", "STRASSE", "ΣΟΦΊΑ", "Ǆemal", "ǅemal", "\u{212A}ilo", "ſun"];
+        let terms = ["élan", "Straße", "σοφία", "ǆemal", "kilo", "sun"];
+        for text in texts {
+            let expected: Vec<&str> = terms
+                .into_iter()
+                .filter(|term| stream_contains(text.as_bytes(), &Query::parse(term, false).unwrap()).unwrap())
+                .collect();
+            assert_eq!(found(&terms, text.as_bytes()), expected, "text {:?}", text);
+        }
+    }
+
+    #[test]
+    fn non_ascii_terms_across_the_chunk_boundary() {
+        for offset in 1..8 {
+            let mut text = vec![b' '; CHUNK_SIZE - offset];
+            text.extend_from_slice("ÉLAN VITAL".as_bytes());
+            assert_eq!(found(&["élan vital"], &text), ["élan vital"], "offset {}", offset);
+        }
+    }
+}
@@ -0,0 +1,108 @@
+// Reverse asset lookup (--uses): find the projects, and the sequences and bins
+// inside them, that reference a given media file.
+
+use std::io;
+use std::path::Path;
+
+use crate::project::{ClipSource, Project};
+use crate::{extract_assets_from_prproj, normalize_asset_path};
+
+/// A media file to look for, normalized like project asset paths
+pub struct MediaTarget {
+    /// Lowercased, '\'-separated path or bare file name
+    needle: String,
+    has_dirs: bool,
+}
+
+impl MediaTarget {
+    pub fn new(file: &str) -> MediaTarget {
+        let needle = normalize_asset_path(file).to_lowercase();
+        let has_dirs = needle.contains('\\');
+        MediaTarget { needle, has_dirs }
+    }
+
+    /// A bare file name matches any folder; a path matches the full asset path or its trailing components
+    pub fn matches(&self, asset: &str) -> bool {
+        let asset = normalize_asset_path(asset).to_lowercase();
+        if self.has_dirs {
+            let needle = self.needle.trim_start_matches(".\\");
+            asset == needle || asset.ends_with(&format!("\\{}", needle))
+        } else {
+            asset.rsplit('\\').next() == Some(self.needle.as_str())
+        }
+    }
+}
+
+pub struct Usage {
+    /// Project assets that matched
+    pub assets: Vec<String>,
+    /// Names of sequences with a track item sourced from a matched file
+    pub sequences: Vec<String>,
+    /// Project panel paths of clips from a matched file, e.g. "Footage/Day 1/A001.mov"
+    pub bins: Vec<String>,
+}
+
+pub fn file_uses(path: &Path, target: &MediaTarget, max_size_bytes: Option<usize>) -> Result<Option<Usage>, io::Error> {
+    let mut assets = extract_assets_from_prproj(path, max_size_bytes)?;
+    assets.retain(|a| target.matches(a));
+    if assets.is_empty() {
+        return Ok(None);
+    }
+
+    // Only projects that reference the file pay for the full model. The asset
+    // extractor tolerates XML the loader rejects, so a load failure keeps the match.
+    let (sequences, bins) = Project::load(path, max_size_bytes)
+        .map(|project| {
+            let used = media_used(&project, target);
+            (sequences_using(&project, &used), bins_using(&project, &used))
+        })
+        .unwrap_or_default();
+
+    Ok(Some(Usage { assets, sequences, bins }))
+}
+
+/// Per project media, whether it is the target file
+fn media_used(project: &Project, target: &MediaTarget) -> Vec<bool> {
+    project
+        .media
+        .iter()
+        .map(|m| {
+            [&m.file_path, &m.actual_media_file_path, &m.relative_path]
+                .into_iter()
+                .flatten()
+                .any(|p| target.matches(p))
+        })
+        .collect()
+}
+
+fn sequences_using(project: &Project, used: &[bool]) -> Vec<String> {
+    project
+        .sequences
+        .iter()
+        .filter(|seq| {
+            seq.video_tracks
+                .iter()
+                .chain(seq.audio_tracks.iter())
+                .flat_map(|t| t.items.iter())
+                .any(|item| matches!(item.source, Some(ClipSource::Media(i)) if used[i]))
+        })
+        .map(|seq| seq.name.clone())
+        .collect()
+}
+
+fn bins_using(project: &Project, used: &[bool]) -> Vec<String> {
+    project
+        .root
+        .item_paths()
+        .into_iter()
+        .filter(|(_, item)| {
+            item.master_clip.is_some_and(|m| {
+                project.master_clips[m]
+                    .clips
+                    .iter()
+                    .any(|clip| matches!(clip.source, Some(ClipSource::Media(i)) if used[i]))
+            })
+        })
+        .map(|(path, _)| path)
+        .collect()
+}
@@ -0,0 +1,199 @@
+// Watch mode (--watch): after the initial scan, re-run the search on projects
+// as they are created, saved or removed, and report what changed.
+
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use crate::errors::categorize;
+use crate::output::{Output, Record};
+use crate::search::Search;
+
+/// Editors write a project in several steps; wait for this much quiet before re-reading it
+const SETTLE_TIME: Duration = Duration::from_millis(750);
+
+/// Identity of a reported result, used to tell new and changed results from repeats
+pub fn record_key(record: &Record) -> String {
+    serde_json::to_string(record).unwrap_or_default()
+}
+
+/// Paths from watch events, held until the events have stopped for SETTLE_TIME
+#[derive(Default)]
+struct Pending {
+    paths: HashSet<PathBuf>,
+    last_event: Option<Instant>,
+}
+
+impl Pending {
+    fn add(&mut self, paths: impl IntoIterator<Item = PathBuf>, now: Instant) {
+        self.paths.extend(paths);
+        self.last_event = Some(now);
+    }
+
+    /// The pending paths in order, once no event has arrived for SETTLE_TIME
+    fn settled(&mut self, now: Instant) -> Vec<PathBuf> {
+        let quiet = self.last_event.is_some_and(|last| now.duration_since(last) >= SETTLE_TIME);
+        if !quiet {
+            return Vec::new();
+        }
+        let mut paths: Vec<PathBuf> = self.paths.drain().collect();
+        paths.sort();
+        paths
+    }
+}
+
+/// Search a changed project again. Returns the record to report: a result that is
+/// new or differs from the last report, or an Unmatched record for a project that
+/// was reported before and no longer matches or is gone.
+fn recheck(path: PathBuf, search: &Search, known: &mut HashMap<PathBuf, String>) -> Option<Record> {
+    let display = path.display().to_string();
+    if !path.exists() {
+        return known.remove(&path).map(|_| Record::Unmatched { path: display, removed: true });
+    }
+    match search.run(&path) {
+        Ok(Some(record)) => {
+            let key = record_key(&record);
+            (known.insert(path, key.clone()) != Some(key)).then_some(record)
+        }
+        Ok(None) => known.remove(&path).map(|_| Record::Unmatched { path: display, removed: false }),
+        Err(e) => Some(Record::Error { path: display, category: categorize(&e), error: e.to_string() }),
+    }
+}
+
+/// Watch `roots` until Ctrl+C. `known` holds the results of the initial scan by path;
+/// `is_target` applies the extension, exclude and size filters of the scan.
+pub fn watch(
+    roots: &[PathBuf],
+    is_target: impl Fn(&Path) -> bool,
+    search: &Search,
+    output: &Output,
+    mut known: HashMap<PathBuf, String>,
+    interrupted: &AtomicBool,
+) -> Result<(), notify::Error> {
+    let (tx, rx) = mpsc::channel::<notify::Result<Event>>();
+    let mut watcher = notify::recommended_watcher(tx)?;
+    for root in roots.iter().filter(|r| r.exists()) {
+        watcher.watch(root, RecursiveMode::Recursive)?;
+    }
+
+    let mut pending = Pending::default();
+
+    while !interrupted.load(Ordering::SeqCst) {
+        match rx.recv_timeout(Duration::from_millis(200)) {
+            Ok(Ok(event)) => {
+                if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)) {
+                    // A rename reports both the old and the new path
+                    pending.add(event.paths.into_iter().filter(|p| is_target(p)), Instant::now());
+                }
+            }
+            Ok(Err(e)) => eprintln!("Warning: watch error: {}", e),
+            Err(mpsc::RecvTimeoutError::Timeout) => {}
+            Err(mpsc::RecvTimeoutError::Disconnected) => break,
+        }
+
+        for path in pending.settled(Instant::now()) {
+            if let Some(record) = recheck(path, search, &mut known) {
+                output.emit(record);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query::Query;
+    use crate::search::Mode;
+    use std::fs;
+
+    fn search(text: &str) -> Search {
+        Search {
+            mode: Mode::Snippets,
+            query: Some(Query::parse(text, false).unwrap()),
+            scopes: None,
+            index: None,
+            max_size_bytes: None,
+            snippet_chars: 0,
+        }
+    }
+
+    fn json(record: Option<Record>) -> serde_json::Value {
+        serde_json::to_value(record).unwrap()
+    }
+
+    #[test]
+    fn record_key_tells_changed_results_from_repeats() {
+        let record = |snippet: &str| Record::Match {
+            path: "a.prproj".to_string(),
+            snippet: Some(snippet.to_string()),
+            scopes: None,
+            occurrences: None,
+        };
+        assert_eq!(record_key(&record("clair de lune")), record_key(&record("clair de lune")));
+        assert_ne!(record_key(&record("clair de lune")), record_key(&record("clair de lune (final)")));
+        let removed = Record::Unmatched { path: "a.prproj".to_string(), removed: true };
+        assert_ne!(record_key(&record("a.prproj")), record_key(&removed));
+    }
+
+    #[test]
+    fn paths_wait_for_the_events_to_settle() {
+        let start = Instant::now();
+        let mut pending = Pending::default();
+        assert!(pending.settled(start + SETTLE_TIME).is_empty());
+
+        pending.add([PathBuf::from("b.prproj"), PathBuf::from("a.prproj")], start);
+        assert!(pending.settled(start + SETTLE_TIME / 2).is_empty());
+        // Every event restarts the wait
+        let later = start + SETTLE_TIME / 2;
+        pending.add([PathBuf::from("a.prproj")], later);
+        assert!(pending.settled(start + SETTLE_TIME).is_empty());
+        assert_eq!(pending.settled(later + SETTLE_TIME), vec![PathBuf::from("a.prproj"), PathBuf::from("b.prproj")]);
+        assert!(pending.settled(later + 2 * SETTLE_TIME).is_empty());
+
+        // Events for filtered-out paths still count as activity
+        pending.add([PathBuf::from("c.prproj")], later);
+        pending.add([], later + SETTLE_TIME);
+        assert!(pending.settled(later + SETTLE_TIME).is_empty());
+    }
+
+    #[test]
+    fn rechecks_report_only_what_changed() {
+        let dir = std::env::temp_dir().join(format!("premiere-hunter-watch-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("edit.prproj");
+        let display = path.display().to_string();
+        let search = search("lune");
+        let mut known = HashMap::new();
+
+        fs::write(&path, "<Name>Clair de Lune</Name>").unwrap();
+        let first = recheck(path.clone(), &search, &mut known);
+        assert_eq!(json(first)["snippet"], "<Name>Clair de Lune</Name>");
+        // Saved again without a change to the result
+        assert!(recheck(path.clone(), &search, &mut known).is_none());
+
+        fs::write(&path, "<Name>Clair de Lune (final)</Name>").unwrap();
+        let changed = recheck(path.clone(), &search, &mut known);
+        assert_eq!(json(changed)["snippet"], "<Name>Clair de Lune (final)</Name>");
+
+        fs::write(&path, "<Name>Gymnopédie</Name>").unwrap();
+        let unmatched = recheck(path.clone(), &search, &mut known);
+        assert_eq!(json(unmatched), serde_json::json!({ "type": "unmatched", "path": display, "removed": false }));
+        assert!(recheck(path.clone(), &search, &mut known).is_none());
+
+        fs::write(&path, "<Name>Lune</Name>").unwrap();
+        assert!(recheck(path.clone(), &search, &mut known).is_some());
+        fs::remove_file(&path).unwrap();
+        let removed = recheck(path.clone(), &search, &mut known);
+        assert_eq!(json(removed), serde_json::json!({ "type": "unmatched", "path": display, "removed": true }));
+        // A project never reported is not reported as removed
+        assert!(recheck(path, &search, &mut known).is_none());
+        assert!(known.is_empty());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}